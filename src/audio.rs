@@ -1,6 +1,11 @@
 use bevy::prelude::*;
-use bevy_kira_audio::{AudioApp, AudioChannel, AudioPlugin, AudioControl, AudioSource};
-use crate::{notes, state::GameState};
+use bevy_kira_audio::{AudioApp, AudioChannel, AudioPlugin, AudioControl, AudioSource, AudioTween, AudioInstance};
+use std::time::Duration;
+use crate::{notes, state::GameState, chart::ChartAsset, library::{SelectedSong, SongLibrary}, select_menu::SongFocus, settings::Settings};
+
+//select menu 미리듣기 길이(초). 끝나면 preview_time부터 다시 재생
+const PREVIEW_LENGTH: f32 = 15.;
+const PREVIEW_FADE_MILLIS: u64 = 700;
 
 
 pub struct GameAudioPlugin;
@@ -14,6 +19,17 @@ impl Plugin for GameAudioPlugin {
         .add_audio_channel::<KeySoundChannel2>()
         .add_audio_channel::<KeySoundChannel3>()
         .add_audio_channel::<KeySoundChannel4>()
+        .init_resource::<SongPreview>()
+        .init_resource::<MainTrackInstance>()
+        .add_system_set(
+            SystemSet::on_update(GameState::SelectMenu)
+            .with_system(play_song_preview))
+        .add_system_set(
+            SystemSet::on_pause(GameState::SelectMenu)
+            .with_system(stop_song_preview))
+        .add_system_set(
+            SystemSet::on_exit(GameState::SelectMenu)
+            .with_system(stop_song_preview))
         .add_system_set(
             SystemSet::on_enter(GameState::InGame)
             .with_system(setup_audio_channel))
@@ -21,6 +37,10 @@ impl Plugin for GameAudioPlugin {
             SystemSet::on_update(GameState::InGame)
             .with_system(control_main_track)
             .with_system(event_key_sound)
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::InGame)
+            .with_system(stop_main_track)
         );
     }
 }
@@ -46,10 +66,10 @@ pub struct ChannelAudioState<T> {
     _marker: std::marker::PhantomData<T>,
 }
 
-impl<T> Default for ChannelAudioState<T> {
-    fn default() -> Self {
+impl<T> ChannelAudioState<T> {
+    fn new(volume: f64) -> Self {
         ChannelAudioState {
-            volume: 0.1,    //Basic : 1
+            volume,    //Basic : 1
             stopped: true,
             paused: false,
             loop_started: false,
@@ -58,6 +78,17 @@ impl<T> Default for ChannelAudioState<T> {
     }
 }
 
+//재생 중인 main track. MusicTimer를 실제 재생 위치에 맞추는 데 사용
+#[derive(Default)]
+pub struct MainTrackInstance(pub Option<Handle<AudioInstance>>);
+
+#[derive(Default)]
+pub struct SongPreview {
+    //재생 중인 audio 경로. library가 다시 정렬되어도 같은 곡인지 알 수 있음
+    audio_path: Option<String>,
+    timer: Timer,
+}
+
 pub struct AudioResource {
     main_track: Handle<AudioSource>,
     hit_sound1: Handle<AudioSource>,
@@ -71,8 +102,14 @@ pub struct AudioResource {
 pub fn setup_audio_channel(
     mut commands: Commands,
     asset_server: ResMut<AssetServer>,
+    selected_song: Res<SelectedSong>,
+    charts: Res<Assets<ChartAsset>>,
+    settings: Res<Settings>,
 ) {
-    let sound_track = asset_server.load("music/PUPA/PUPA.mp3");
+    let sound_track = match selected_song.get(&asset_server, &charts) {
+        Some(Ok(chart)) => asset_server.load(&selected_song.audio_path(chart)),
+        _ => Handle::default(),
+    };
     let hit1 = asset_server.load("music/hit_sound/key1.ogg");
     let hit2 = asset_server.load("music/hit_sound/key2.ogg");
     let hit3 = asset_server.load("music/hit_sound/key3.ogg");
@@ -85,11 +122,12 @@ pub fn setup_audio_channel(
         hit_sound3: hit3,
         hit_sound4: hit4
     });
-    commands.insert_resource(ChannelAudioState::<MainTrackChannel>::default());
-    commands.insert_resource(ChannelAudioState::<KeySoundChannel1>::default());
-    commands.insert_resource(ChannelAudioState::<KeySoundChannel2>::default());
-    commands.insert_resource(ChannelAudioState::<KeySoundChannel3>::default());
-    commands.insert_resource(ChannelAudioState::<KeySoundChannel4>::default());
+    commands.insert_resource(MainTrackInstance::default());
+    commands.insert_resource(ChannelAudioState::<MainTrackChannel>::new(settings.volume));
+    commands.insert_resource(ChannelAudioState::<KeySoundChannel1>::new(settings.volume));
+    commands.insert_resource(ChannelAudioState::<KeySoundChannel2>::new(settings.volume));
+    commands.insert_resource(ChannelAudioState::<KeySoundChannel3>::new(settings.volume));
+    commands.insert_resource(ChannelAudioState::<KeySoundChannel4>::new(settings.volume));
     
 }
 
@@ -134,6 +172,7 @@ pub fn event_key_sound(
 pub fn control_main_track(
     audio_channel: Res<AudioChannel<MainTrackChannel>>,
     mut audio_state: ResMut<ChannelAudioState<MainTrackChannel>>,
+    mut main_track: ResMut<MainTrackInstance>,
     audio_source: Res<AudioResource>,
     hold_timer: Query<(&notes::MusicTimer, Without<notes::Hold>)>,
 ) {
@@ -141,7 +180,7 @@ pub fn control_main_track(
 
     if timer.timer.elapsed_secs() > 0.{
         if audio_state.stopped == true {
-            audio_channel.play(audio_source.main_track.clone());
+            main_track.0 = Some(audio_channel.play(audio_source.main_track.clone()).handle());
             audio_channel.set_volume(audio_state.volume);
             audio_state.stopped = false;
             println!("Play Music");
@@ -158,4 +197,52 @@ pub fn control_main_track(
             println!("Music Resumed");
         }
     }
-}
\ No newline at end of file
+}
+
+pub fn stop_main_track(
+    audio_channel: Res<AudioChannel<MainTrackChannel>>,
+) {
+    audio_channel.stop();
+}
+
+//focus된 곡을 MainTrackChannel로 미리듣기
+pub fn play_song_preview(
+    asset_server: Res<AssetServer>,
+    audio_channel: Res<AudioChannel<MainTrackChannel>>,
+    library: Res<SongLibrary>,
+    focus: Res<SongFocus>,
+    mut preview: ResMut<SongPreview>,
+    time: Res<Time>,
+    settings: Res<Settings>,
+) {
+    preview.timer.tick(time.delta());
+    let song = match library.songs.get(focus.song) {
+        Some(song) => song,
+        None => return,
+    };
+    //chart가 아직 load되지 않았으면 다음 frame에 다시 시도
+    let audio_path = match &song.audio_path {
+        Some(audio_path) => audio_path,
+        None => return,
+    };
+    if preview.audio_path.as_ref() == Some(audio_path) && !preview.timer.finished() {
+        return;
+    }
+
+    audio_channel.stop().fade_out(AudioTween::linear(Duration::from_millis(PREVIEW_FADE_MILLIS)));
+    audio_channel.play(asset_server.load(audio_path.as_str()))
+        .start_from(song.preview_time as f64)
+        .fade_in(AudioTween::linear(Duration::from_millis(PREVIEW_FADE_MILLIS)));
+    audio_channel.set_volume(settings.volume);
+
+    preview.audio_path = Some(audio_path.clone());
+    preview.timer = Timer::from_seconds(PREVIEW_LENGTH, false);
+}
+
+pub fn stop_song_preview(
+    audio_channel: Res<AudioChannel<MainTrackChannel>>,
+    mut preview: ResMut<SongPreview>,
+) {
+    audio_channel.stop().fade_out(AudioTween::linear(Duration::from_millis(PREVIEW_FADE_MILLIS)));
+    preview.audio_path = None;
+}
@@ -0,0 +1,247 @@
+use bevy::prelude::*;
+use bevy_kira_audio::{AudioChannel, AudioControl, AudioSource};
+use crate::state::{GameState, take_key, pop_on_keys};
+use crate::notes::{FontResource, Press4Key};
+use crate::lane_input::LaneInput;
+use crate::settings::Settings;
+use crate::audio::MainTrackChannel;
+
+const METRONOME_BPM: f32 = 120.;
+//처음 몇 박은 박자에 익숙해지도록 tap을 기록하지 않음
+const LEAD_IN_BEATS: usize = 4;
+const TAP_COUNT: usize = 16;
+//이보다 크게 벗어난 tap(ms)은 박자를 놓친 것으로 보고 버림
+const MAX_TAP_ERROR: f32 = 250.;
+const TEXT_COLOR: Color = Color::rgba(0.9, 0.9, 0.9, 1.);
+const BEAT_COLOR: Color = Color::rgba(0.9, 0.55, 0.2, 0.9);
+
+pub struct CalibrationPlugin;
+
+impl Plugin for CalibrationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Calibration>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Calibration)
+                .with_system(setup_calibration)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Calibration)
+                .with_system(play_metronome)
+                .with_system(record_tap.after(play_metronome))
+                .with_system(calibration_keyboard)
+                .with_system(update_calibration_screen.after(record_tap))
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Calibration)
+                .with_system(despawn_calibration)
+            );
+    }
+}
+
+//metronome을 재생하며 tap과 click 사이의 차이를 모음
+#[derive(Default)]
+pub struct Calibration {
+    click: Handle<AudioSource>,
+    //calibration을 시작한 후 지난 시간(초)
+    elapsed: f32,
+    next_beat: usize,
+    //실제로 click을 재생한 시각(초). frame 단위로 늦게 재생된 만큼을 포함
+    clicks: Vec<f32>,
+    //tap 시각 - 가장 가까운 click 시각 (ms)
+    taps: Vec<f32>,
+}
+
+impl Calibration {
+    fn average(&self) -> Option<f32> {
+        if self.taps.is_empty() {
+            None
+        } else {
+            Some(self.taps.iter().sum::<f32>() / self.taps.len() as f32)
+        }
+    }
+
+    fn finished(&self) -> bool {
+        self.taps.len() >= TAP_COUNT
+    }
+}
+
+#[derive(Component)]
+pub struct CalibrationScreen;
+
+#[derive(Component)]
+pub struct CalibrationText;
+
+#[derive(Component)]
+pub struct BeatIndicator;
+
+pub fn setup_calibration(
+    mut commands: Commands,
+    font_resource: Res<FontResource>,
+    asset_server: Res<AssetServer>,
+    mut calibration: ResMut<Calibration>,
+) {
+    *calibration = Calibration {
+        click: asset_server.load("music/hit_sound/key1.ogg"),
+        ..Default::default()
+    };
+
+    commands.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        parent.spawn_bundle(TextBundle {
+            style: Style {
+                margin: UiRect::all(Val::Px(30.)),
+                ..Default::default()
+            },
+            text: Text::from_section(
+                "Offset Calibration",
+                TextStyle {
+                    font: font_resource.font.clone(),
+                    font_size: 50.,
+                    color: TEXT_COLOR,
+                }),
+            ..Default::default()
+        });
+        parent.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(80.), Val::Px(80.)),
+                margin: UiRect::all(Val::Px(20.)),
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        }).insert(BeatIndicator);
+        parent.spawn_bundle(TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font_resource.font.clone(),
+                    font_size: 24.,
+                    color: TEXT_COLOR,
+                }).with_alignment(TextAlignment::CENTER),
+            ..Default::default()
+        }).insert(CalibrationText);
+    }).insert(CalibrationScreen);
+}
+
+pub fn play_metronome(
+    time: Res<Time>,
+    mut calibration: ResMut<Calibration>,
+    audio_channel: Res<AudioChannel<MainTrackChannel>>,
+    settings: Res<Settings>,
+) {
+    calibration.elapsed += time.delta_seconds();
+    if calibration.finished() {
+        return;
+    }
+
+    let beat_length = 60. / METRONOME_BPM;
+    if calibration.elapsed >= calibration.next_beat as f32 * beat_length {
+        audio_channel.play(calibration.click.clone());
+        audio_channel.set_volume(settings.volume);
+        let elapsed = calibration.elapsed;
+        calibration.clicks.push(elapsed);
+        calibration.next_beat += 1;
+    }
+}
+
+pub fn record_tap(
+    key_input: Res<Input<KeyCode>>,
+    lane_input: Res<LaneInput>,
+    mut calibration: ResMut<Calibration>,
+) {
+    let lanes = [Press4Key::First, Press4Key::Second, Press4Key::Third, Press4Key::Fourth];
+    let tapped = key_input.just_pressed(KeyCode::Space) ||
+        lanes.iter().any(|lane| lane_input.just_pressed(lane));
+    if !tapped || calibration.finished() || calibration.clicks.len() <= LEAD_IN_BEATS {
+        return;
+    }
+
+    let elapsed = calibration.elapsed;
+    let nearest = calibration.clicks.iter()
+        .map(|click| (elapsed - click) * 1000.)
+        .min_by(|a, b| a.abs().partial_cmp(&b.abs()).unwrap());
+    if let Some(offset) = nearest {
+        if offset.abs() <= MAX_TAP_ERROR {
+            calibration.taps.push(offset);
+        }
+    }
+}
+
+pub fn calibration_keyboard(
+    mut key_input: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+    mut calibration: ResMut<Calibration>,
+    mut settings: ResMut<Settings>,
+) {
+    if pop_on_keys(&mut key_input, &mut state, &[KeyCode::Escape]) {
+        return;
+    }
+    if key_input.just_pressed(KeyCode::R) {
+        let click = calibration.click.clone();
+        *calibration = Calibration {
+            click,
+            ..Default::default()
+        };
+    } else if calibration.finished() && take_key(&mut key_input, KeyCode::Return) {
+        if let Some(average) = calibration.average() {
+            settings.audio_offset = average.round() as i32;
+            settings.save();
+        }
+        state.pop().unwrap();
+    }
+}
+
+pub fn update_calibration_screen(
+    calibration: Res<Calibration>,
+    mut text_query: Query<&mut Text, With<CalibrationText>>,
+    mut indicator_query: Query<&mut UiColor, With<BeatIndicator>>,
+) {
+    let beat_length = 60. / METRONOME_BPM;
+    for mut color in indicator_query.iter_mut() {
+        //click 직후 잠깐 켜짐
+        let since_click = calibration.clicks.last().map(|click| calibration.elapsed - click);
+        color.0 = match since_click {
+            Some(since_click) if !calibration.finished() && since_click < beat_length / 4. => BEAT_COLOR,
+            _ => Color::NONE,
+        };
+    }
+
+    let average = match calibration.average() {
+        Some(average) => format!("{:+.0} ms", average),
+        None => "-".to_string(),
+    };
+    let message = if calibration.finished() {
+        format!("Average offset  {}\n\nEnter to apply, R to retry, Esc to cancel", average)
+    } else if calibration.clicks.len() <= LEAD_IN_BEATS {
+        "Listen to the metronome...\n\nEsc to cancel".to_string()
+    } else {
+        format!(
+            "Tap any lane key or Space on the beat  ({}/{})\n\nAverage offset  {}\n\nR to retry, Esc to cancel",
+            calibration.taps.len(), TAP_COUNT, average
+        )
+    };
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+}
+
+pub fn despawn_calibration(
+    mut commands: Commands,
+    query: Query<Entity, With<CalibrationScreen>>,
+    audio_channel: Res<AudioChannel<MainTrackChannel>>,
+) {
+    audio_channel.stop();
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
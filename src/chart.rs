@@ -0,0 +1,441 @@
+use bevy::prelude::*;
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset, BoxedFuture};
+use bevy::reflect::TypeUuid;
+use std::fmt;
+use crate::notes::{self, Note};
+use crate::timing::{TimingMap, TimingPoint};
+use crate::scroll::{ScrollMap, ScrollPoint};
+
+const CHART_HEADER: &str = "rhythme chart v";
+//v2: [TimingPoints] section과 beat 단위 note timing 추가
+//v3: [ScrollVelocity] section 추가
+pub const CHART_VERSION: u32 = 3;
+//이보다 큰 BPM은 마디 길이가 너무 짧아서 마디선을 만들 수 없음
+const MAX_BPM: f32 = 10000.;
+
+#[derive(Clone)]
+pub struct ChartMetadata {
+    pub title: String,
+    pub artist: String,
+    //chart 파일과 같은 폴더 기준의 상대경로
+    pub audio: String,
+    pub bpm: f32,
+    //ms
+    pub offset: i32,
+    pub difficulty: String,
+    pub level: u32,
+    //select menu에서 미리듣기를 시작할 위치(ms)
+    pub preview_time: usize,
+}
+
+impl Default for ChartMetadata {
+    fn default() -> Self {
+        ChartMetadata {
+            title: String::new(),
+            artist: String::new(),
+            audio: String::new(),
+            bpm: 120.,
+            offset: 0,
+            difficulty: "Normal".to_string(),
+            level: 0,
+            preview_time: 0,
+        }
+    }
+}
+
+pub struct ChartFile {
+    pub version: u32,
+    pub metadata: ChartMetadata,
+    pub timing: TimingMap,
+    pub scroll: ScrollMap,
+    pub notes: Vec<Note>,
+}
+
+enum ChartSection {
+    None,
+    Metadata,
+    TimingPoints,
+    ScrollVelocity,
+    Notes,
+}
+
+//line, column은 1부터 시작. 0이면 특정 위치가 없는 file 단위의 오류
+#[derive(Clone, Debug)]
+pub struct ChartParseError {
+    pub line: usize,
+    pub column: usize,
+    pub reason: String,
+}
+
+impl ChartParseError {
+    pub fn new(line: usize, column: usize, reason: String) -> Self {
+        ChartParseError { line, column, reason }
+    }
+}
+
+impl fmt::Display for ChartParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.reason)
+        } else {
+            write!(f, "line {}, column {}: {}", self.line, self.column, self.reason)
+        }
+    }
+}
+
+pub struct ChartPlugin;
+
+impl Plugin for ChartPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_asset::<ChartAsset>()
+            .init_asset_loader::<ChartLoader>();
+    }
+}
+
+//parse 오류도 asset에 저장하여 오류 화면에서 보여줄 수 있게 함
+#[derive(TypeUuid)]
+#[uuid = "2863d6de-89ee-4d31-b89b-2c0b8c6303cb"]
+pub struct ChartAsset {
+    pub chart: Result<ChartFile, Vec<ChartParseError>>,
+}
+
+#[derive(Default)]
+pub struct ChartLoader;
+
+impl AssetLoader for ChartLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let chart = match std::str::from_utf8(bytes) {
+                Ok(string) => parse_chart(string),
+                Err(error) => Err(vec![ChartParseError::new(0, 0, format!("chart is not valid UTF-8: {}", error))]),
+            };
+            load_context.set_default_asset(LoadedAsset::new(ChartAsset { chart }));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["chart"]
+    }
+}
+
+//chart 형식
+//  rhythme chart v3
+//
+//  [Metadata]
+//  Title: PUPA
+//  Audio: PUPA.mp3
+//  ...
+//
+//  [TimingPoints]
+//  time,bpm,meter
+//
+//  [ScrollVelocity]
+//  time,multiplier
+//
+//  [Notes]
+//  lane,Short|Long,timing,release,
+//
+//timing, release, ScrollVelocity의 time은 ms 또는 'b'로 시작하는 beat(예: b12.5)
+//multiplier는 다음 줄의 time까지 적용되며 0이면 멈추고 음수면 거꾸로 흐름
+//[TimingPoints]가 없으면 Metadata의 BPM, Offset을 사용
+//한 줄에서 오류가 나도 멈추지 않고 file 전체의 오류를 모아서 반환
+pub fn parse_chart(string: &str) -> Result<ChartFile, Vec<ChartParseError>> {
+    let mut errors: Vec<ChartParseError> = Vec::new();
+    let mut version = CHART_VERSION;
+    let mut metadata = ChartMetadata::default();
+    let mut timing_points: Vec<TimingPoint> = Vec::new();
+    //beat 단위 timing은 모든 timing point를 읽은 뒤에 변환할 수 있으므로 note 줄은 나중에 parse
+    let mut note_lines: Vec<(usize, usize, &str)> = Vec::new();
+    let mut scroll_lines: Vec<(usize, usize, &str)> = Vec::new();
+    let mut section = ChartSection::None;
+    let mut header_found = false;
+
+    for (index, raw_line) in string.lines().enumerate() {
+        let line_number = index + 1;
+        let line = raw_line.trim();
+        //trim으로 잘려나간 앞쪽 공백만큼 column을 보정
+        let indent = raw_line.len() - raw_line.trim_start().len();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+
+        if !header_found {
+            header_found = true;
+            match parse_header(line) {
+                Ok(chart_version) => version = chart_version,
+                Err(reason) => errors.push(ChartParseError::new(line_number, indent + 1, reason)),
+            }
+            //header 없이 section부터 시작하면 그 줄은 section으로 계속 읽음
+            if !line.starts_with('[') {
+                continue;
+            }
+        }
+
+        if line.starts_with('[') {
+            section = match line {
+                "[Metadata]" => ChartSection::Metadata,
+                "[TimingPoints]" => ChartSection::TimingPoints,
+                "[ScrollVelocity]" => ChartSection::ScrollVelocity,
+                "[Notes]" => ChartSection::Notes,
+                _ => {
+                    errors.push(ChartParseError::new(line_number, indent + 1, format!("unknown section {}", line)));
+                    ChartSection::None
+                },
+            };
+            continue;
+        }
+
+        let result = match section {
+            ChartSection::Metadata => parse_metadata_line(line, &mut metadata),
+            ChartSection::TimingPoints => parse_timing_point_line(line).map(|point| timing_points.push(point)),
+            ChartSection::ScrollVelocity => {
+                scroll_lines.push((line_number, indent, line));
+                Ok(())
+            },
+            ChartSection::Notes => {
+                note_lines.push((line_number, indent, line));
+                Ok(())
+            },
+            ChartSection::None => Err((0, "line is outside of a section".to_string())),
+        };
+        if let Err((column, reason)) = result {
+            errors.push(ChartParseError::new(line_number, indent + column + 1, reason));
+        }
+    }
+
+    if !header_found {
+        errors.push(ChartParseError::new(1, 1, "missing chart header".to_string()));
+    }
+
+    let timing = if timing_points.is_empty() {
+        TimingMap::from_metadata(metadata.bpm, metadata.offset)
+    } else {
+        TimingMap::new(timing_points)
+    };
+    let mut scroll_points: Vec<ScrollPoint> = Vec::new();
+    for (line_number, indent, line) in scroll_lines {
+        match parse_scroll_line(line, &timing) {
+            Ok(point) => scroll_points.push(point),
+            Err((column, reason)) => errors.push(ChartParseError::new(line_number, indent + column + 1, reason)),
+        }
+    }
+    let mut chart = ChartFile {
+        version,
+        metadata,
+        timing,
+        scroll: ScrollMap::new(scroll_points),
+        notes: Vec::new(),
+    };
+    for (line_number, indent, line) in note_lines {
+        match notes::parse_file_string(line, &chart.timing) {
+            Ok(note) => chart.notes.push(note),
+            Err((column, reason)) => errors.push(ChartParseError::new(line_number, indent + column + 1, reason)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(chart)
+    } else {
+        errors.sort_by(|a, b| (a.line, a.column).cmp(&(b.line, b.column)));
+        Err(errors)
+    }
+}
+
+fn parse_header(line: &str) -> Result<u32, String> {
+    if !line.starts_with(CHART_HEADER) {
+        return Err(format!("expected '{}{}' header", CHART_HEADER, CHART_VERSION));
+    }
+    let version: u32 = line[CHART_HEADER.len()..].parse().map_err(|_| format!("invalid chart version in '{}'", line))?;
+    if version > CHART_VERSION {
+        return Err(format!("unsupported chart version {}, newest is {}", version, CHART_VERSION));
+    }
+    Ok(version)
+}
+
+//time,bpm,meter 형식. meter는 생략하면 4
+fn parse_timing_point_line(line: &str) -> Result<TimingPoint, (usize, String)> {
+    let mut fields: Vec<(usize, &str)> = Vec::new();
+    let mut start: usize = 0;
+    for field in line.split(',') {
+        fields.push((start + field.len() - field.trim_start().len(), field.trim()));
+        start += field.len() + 1;
+    }
+    if fields.len() < 2 {
+        return Err((line.len(), "expected 'time,bpm,meter'".to_string()));
+    }
+
+    let (column, field) = fields[0];
+    let time: f32 = field.parse().map_err(|_| (column, format!("invalid time '{}'", field)))?;
+    if !time.is_finite() {
+        return Err((column, format!("invalid time '{}'", field)));
+    }
+    let (column, field) = fields[1];
+    let bpm = parse_bpm(field, column)?;
+    let meter = match fields.get(2) {
+        Some(&(column, field)) if !field.is_empty() => {
+            let meter: u32 = field.parse().map_err(|_| (column, format!("invalid meter '{}'", field)))?;
+            if meter == 0 {
+                return Err((column, "meter must be greater than 0".to_string()));
+            }
+            meter
+        },
+        _ => 4,
+    };
+    Ok(TimingPoint { time, bpm, meter })
+}
+
+fn parse_bpm(field: &str, column: usize) -> Result<f32, (usize, String)> {
+    let bpm: f32 = field.parse().map_err(|_| (column, format!("invalid BPM '{}'", field)))?;
+    //NaN은 비교가 항상 false이므로 is_finite로 먼저 거름
+    if !bpm.is_finite() || bpm <= 0. || bpm > MAX_BPM {
+        return Err((column, format!("BPM must be greater than 0 and at most {}", MAX_BPM)));
+    }
+    Ok(bpm)
+}
+
+//time,multiplier 형식
+fn parse_scroll_line(line: &str, timing: &TimingMap) -> Result<ScrollPoint, (usize, String)> {
+    let (time, multiplier) = line.split_once(',').ok_or_else(|| (line.len(), "expected 'time,multiplier'".to_string()))?;
+    let column = time.len() + 1 + (multiplier.len() - multiplier.trim_start().len());
+    let time = time.trim();
+    let multiplier = multiplier.trim().trim_end_matches(',').trim();
+
+    let time = timing.parse_time(time).ok_or_else(|| (0, format!("invalid time '{}'", time)))?;
+    let multiplier: f32 = multiplier.parse().map_err(|_| (column, format!("invalid multiplier '{}'", multiplier)))?;
+    //NaN, inf이면 note 위치를 계산할 수 없음
+    if !multiplier.is_finite() {
+        return Err((column, format!("invalid multiplier '{}'", multiplier)));
+    }
+    Ok(ScrollPoint { time: time as f32, multiplier })
+}
+
+//실패시 (column, 이유)를 반환
+fn parse_metadata_line(line: &str, metadata: &mut ChartMetadata) -> Result<(), (usize, String)> {
+    let (key, value) = line.split_once(':').ok_or_else(|| (0, "expected 'Key: Value'".to_string()))?;
+    let column = key.len() + 1 + (value.len() - value.trim_start().len());
+    let value = value.trim();
+    match key.trim() {
+        "Title" => metadata.title = value.to_string(),
+        "Artist" => metadata.artist = value.to_string(),
+        "Audio" => metadata.audio = value.to_string(),
+        "BPM" => metadata.bpm = parse_bpm(value, column)?,
+        "Offset" => metadata.offset = value.parse().map_err(|_| (column, format!("invalid Offset '{}'", value)))?,
+        "Difficulty" => metadata.difficulty = value.to_string(),
+        "Level" => metadata.level = value.parse().map_err(|_| (column, format!("invalid Level '{}'", value)))?,
+        "PreviewTime" => metadata.preview_time = value.parse().map_err(|_| (column, format!("invalid PreviewTime '{}'", value)))?,
+        //이후 버전에서 추가된 key는 무시
+        _ => (),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notes::{NoteType, Press4Key};
+
+    fn chart(lines: &[&str]) -> String {
+        lines.join("\n")
+    }
+
+    fn parse_ok(string: &str) -> ChartFile {
+        match parse_chart(string) {
+            Ok(chart) => chart,
+            Err(errors) => panic!("unexpected errors: {:?}", errors),
+        }
+    }
+
+    //(line, column) 목록
+    fn error_positions(string: &str) -> Vec<(usize, usize)> {
+        match parse_chart(string) {
+            Ok(_) => panic!("chart should not parse"),
+            Err(errors) => errors.iter().map(|error| (error.line, error.column)).collect(),
+        }
+    }
+
+    #[test]
+    fn parses_sections_and_beat_timings() {
+        let chart = parse_ok(&chart(&[
+            "rhythme chart v3",
+            "",
+            "[Metadata]",
+            "Title: Test",
+            "Level: 7",
+            "",
+            "[TimingPoints]",
+            "0,120,4",
+            "2000,60,4",
+            "",
+            "[ScrollVelocity]",
+            "b4,0.5",
+            "",
+            "[Notes]",
+            "0,Short,b1,",
+            "3,Long,b2,b5,",
+        ]));
+        assert_eq!(chart.version, 3);
+        assert_eq!(chart.metadata.title, "Test");
+        assert_eq!(chart.metadata.level, 7);
+        assert_eq!(chart.notes.len(), 2);
+        assert_eq!(chart.notes[0].timing, 500);
+        let long = &chart.notes[1];
+        assert!(long.note_type == NoteType::Long && long.press_key == Press4Key::Fourth);
+        assert_eq!((long.timing, long.release_timing), (1000, 3000));
+    }
+
+    #[test]
+    fn missing_header_keeps_first_section() {
+        //[Metadata]를 header로 먹어버리면 Title 줄이 section 밖이 되어 오류가 하나 더 생김
+        let string = chart(&[
+            "[Metadata]",
+            "Title: Test",
+            "[Notes]",
+            "0,Short,100,",
+        ]);
+        assert_eq!(error_positions(&string), vec![(1, 1)]);
+        assert_eq!(error_positions(""), vec![(1, 1)]);
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let string = chart(&["rhythme chart v4", "[Notes]", "0,Short,100,"]);
+        match parse_chart(&string) {
+            Ok(_) => panic!("chart should not parse"),
+            Err(errors) => assert_eq!(errors[0].reason, "unsupported chart version 4, newest is 3"),
+        }
+    }
+
+    #[test]
+    fn rejects_non_finite_values() {
+        let string = chart(&[
+            "rhythme chart v3",
+            "[Metadata]",
+            "BPM: inf",
+            "[TimingPoints]",
+            "NaN,120,4",
+            "0,1e12,4",
+            "[ScrollVelocity]",
+            "0,NaN",
+        ]);
+        assert_eq!(error_positions(&string), vec![(3, 6), (5, 1), (6, 3), (8, 3)]);
+    }
+
+    #[test]
+    fn collects_errors_from_every_line() {
+        let string = chart(&[
+            "rhythme chart v3",
+            "[Notes]",
+            "4,Short,100,",
+            "0,Tap,200,",
+            "0,Long,300,",
+            "0,Short,400,",
+        ]);
+        assert_eq!(error_positions(&string), vec![(3, 1), (4, 3), (5, 12)]);
+    }
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use crate::state::{GameState, pop_on_keys};
+use crate::notes::FontResource;
+use crate::chart::ChartAsset;
+use crate::library::SelectedSong;
+
+//화면에 표시할 최대 오류 개수
+const MAX_SHOWN_ERRORS: usize = 30;
+
+pub struct ChartErrorPlugin;
+
+#[derive(Component)]
+pub struct ChartErrorScreen;
+
+impl Plugin for ChartErrorPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_system_set(
+                SystemSet::on_enter(GameState::ChartError)
+                .with_system(setup_error_screen)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::ChartError)
+                .with_system(close_error_screen)
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::ChartError)
+                .with_system(despawn_error_screen)
+            );
+    }
+}
+
+pub fn setup_error_screen(
+    mut commands: Commands,
+    font_resource: Res<FontResource>,
+    selected_song: Res<SelectedSong>,
+    asset_server: Res<AssetServer>,
+    charts: Res<Assets<ChartAsset>>,
+) {
+    let errors = match selected_song.get(&asset_server, &charts) {
+        Some(Err(errors)) => errors,
+        _ => return,
+    };
+
+    let title_style = TextStyle {
+        font: font_resource.font.clone(),
+        font_size: 30.,
+        color: Color::rgba(0.9, 0.3, 0.3, 1.),
+    };
+    let error_style = TextStyle {
+        font: font_resource.font.clone(),
+        font_size: 18.,
+        color: Color::rgba(0.9, 0.9, 0.9, 1.),
+    };
+
+    let mut sections = vec![
+        TextSection {
+            value: format!("Failed to load {} ({} errors)\n\n", selected_song.chart_path, errors.len()),
+            style: title_style,
+        }
+    ];
+    for error in errors.iter().take(MAX_SHOWN_ERRORS) {
+        sections.push(TextSection {
+            value: format!("{}\n", error),
+            style: error_style.clone(),
+        });
+    }
+    if errors.len() > MAX_SHOWN_ERRORS {
+        sections.push(TextSection {
+            value: format!("... and {} more\n", errors.len() - MAX_SHOWN_ERRORS),
+            style: error_style.clone(),
+        });
+    }
+    sections.push(TextSection {
+        value: "\nPress Esc or Enter to go back".to_string(),
+        style: error_style,
+    });
+
+    commands.spawn_bundle(TextBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect::new(Val::Px(30.), Val::Auto, Val::Px(30.), Val::Auto),
+            max_size: Size::new(Val::Px(940.), Val::Undefined),
+            ..Default::default()
+        },
+        text: Text {
+            sections,
+            ..Default::default()
+        },
+        ..Default::default()
+    }).insert(ChartErrorScreen);
+}
+
+pub fn close_error_screen(
+    mut key_input: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    pop_on_keys(&mut key_input, &mut state, &[KeyCode::Escape, KeyCode::Return]);
+}
+
+pub fn despawn_error_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<ChartErrorScreen>>
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,94 @@
+use crate::chart::ChartFile;
+use crate::notes::{Note, NoteType};
+
+//strain graph 한 구간의 길이(ms)
+pub const SECTION_LENGTH: usize = 1000;
+//1초 동안 strain이 줄어드는 비율
+const STRAIN_DECAY: f32 = 0.3;
+//이 간격(ms)보다 빠르게 같은 lane을 연타하면 jack으로 취급
+const JACK_THRESHOLD: f32 = 250.;
+const JACK_WEIGHT: f32 = 0.8;
+//이 간격(ms) 안에 있는 note는 동시에 누르는 chord로 취급
+const CHORD_WINDOW: usize = 10;
+const CHORD_WEIGHT: f32 = 0.4;
+//다른 lane의 long note를 누르고 있는 동안 치는 note에 더해지는 값
+const HOLD_WEIGHT: f32 = 0.3;
+const LONG_NOTE_WEIGHT: f32 = 0.2;
+//구간별 strain을 큰 순서로 정렬한 뒤 i번째 구간에 STRAIN_WEIGHT^i를 곱해서 더함
+const STRAIN_WEIGHT: f32 = 0.9;
+const STAR_SCALE: f32 = 0.06;
+
+pub struct DifficultyRating {
+    pub stars: f32,
+    //SECTION_LENGTH마다의 최대 strain
+    pub strain: Vec<f32>,
+}
+
+//note 밀도, jack/chord 패턴, long note 겹침으로 chart의 난이도를 계산
+pub fn calculate_difficulty(chart: &ChartFile) -> DifficultyRating {
+    let mut notes: Vec<&Note> = chart.notes.iter().collect();
+    notes.sort_by(|a, b| a.timing.cmp(&b.timing));
+
+    let last_timing = notes.iter().map(|note| note.release_timing).max().unwrap_or(0);
+    let mut strain_graph = vec![0.; last_timing / SECTION_LENGTH + 1];
+    let mut lane_timing: [Option<usize>; 4] = [None; 4];
+    let mut strain: f32 = 0.;
+    let mut previous_timing: usize = 0;
+    //timing 순으로 정렬되어 있으므로 chord 범위의 시작과 끝은 앞으로만 움직임
+    let mut chord_start = 0;
+    let mut chord_end = 0;
+    //아직 release_timing이 지나지 않은 long note
+    let mut holds: Vec<&Note> = Vec::new();
+
+    for &note in notes.iter() {
+        let lane = note.press_key.clone() as usize;
+        let mut value: f32 = 1.;
+
+        if let Some(timing) = lane_timing[lane] {
+            let gap = note.timing.saturating_sub(timing).max(1) as f32;
+            if gap < JACK_THRESHOLD {
+                value += JACK_WEIGHT * JACK_THRESHOLD / gap;
+            }
+        }
+
+        while notes[chord_start].timing + CHORD_WINDOW < note.timing {
+            chord_start += 1;
+        }
+        while chord_end < notes.len() && notes[chord_end].timing <= note.timing + CHORD_WINDOW {
+            chord_end += 1;
+        }
+        let chord = chord_end - chord_start - 1;
+        value += CHORD_WEIGHT * chord as f32;
+
+        holds.retain(|hold| note.timing < hold.release_timing);
+        let holding = holds.iter()
+            .filter(|hold| hold.press_key != note.press_key && hold.timing < note.timing)
+            .count();
+        value += HOLD_WEIGHT * holding as f32;
+
+        if note.note_type == NoteType::Long {
+            value += LONG_NOTE_WEIGHT;
+            holds.push(note);
+        }
+
+        let elapsed = (note.timing - previous_timing) as f32 / 1000.;
+        strain = strain * STRAIN_DECAY.powf(elapsed) + value;
+        previous_timing = note.timing;
+        lane_timing[lane] = Some(note.release_timing);
+
+        let section = &mut strain_graph[note.timing / SECTION_LENGTH];
+        *section = section.max(strain);
+    }
+
+    let mut sorted = strain_graph.clone();
+    sorted.sort_by(|a, b| b.partial_cmp(a).unwrap());
+    let weighted: f32 = sorted.iter()
+        .enumerate()
+        .map(|(index, strain)| strain * STRAIN_WEIGHT.powi(index as i32))
+        .sum();
+
+    DifficultyRating {
+        stars: weighted * STAR_SCALE,
+        strain: strain_graph,
+    }
+}
@@ -0,0 +1,243 @@
+use bevy::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::state::GameState;
+use crate::notes::{JudgeAccuracy, Press4Key};
+use crate::settings::Settings;
+
+pub struct JudgePlugin;
+
+impl Plugin for JudgePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<JudgeWindows>()
+            .init_resource::<HitLog>()
+            .add_event::<HitEvent>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame)
+                .with_system(setup_judge_windows)
+                .with_system(reset_hit_log)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                .with_system(record_hits)
+            );
+    }
+}
+
+//설정에서 고르는 판정 범위 묶음
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum JudgePreset {
+    Lenient,
+    Standard,
+    Strict,
+}
+
+impl JudgePreset {
+    pub const ALL: [JudgePreset; 3] = [JudgePreset::Lenient, JudgePreset::Standard, JudgePreset::Strict];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            JudgePreset::Lenient => "Lenient",
+            JudgePreset::Standard => "Standard",
+            JudgePreset::Strict => "Strict",
+        }
+    }
+}
+
+impl Default for JudgePreset {
+    fn default() -> Self {
+        JudgePreset::Standard
+    }
+}
+
+//판정 sprite 옆에 EARLY/LATE를 표시할 등급
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EarlyLateDisplay {
+    All,
+    NonPerfect,
+    Never,
+}
+
+impl EarlyLateDisplay {
+    pub const ALL: [EarlyLateDisplay; 3] = [EarlyLateDisplay::All, EarlyLateDisplay::NonPerfect, EarlyLateDisplay::Never];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EarlyLateDisplay::All => "All",
+            EarlyLateDisplay::NonPerfect => "Non-Perfect",
+            EarlyLateDisplay::Never => "Never",
+        }
+    }
+
+    pub fn shows(&self, accuracy: JudgeAccuracy) -> bool {
+        match self {
+            EarlyLateDisplay::All => true,
+            EarlyLateDisplay::NonPerfect => accuracy != JudgeAccuracy::Perfect,
+            EarlyLateDisplay::Never => false,
+        }
+    }
+}
+
+impl Default for EarlyLateDisplay {
+    fn default() -> Self {
+        EarlyLateDisplay::NonPerfect
+    }
+}
+
+//등급별 허용 오차(초). |입력 시각 - note timing|이 window 이하이면 그 등급
+//bad보다 크고 miss 이하면 Miss, miss보다 이르면 입력을 무시하고 miss보다 늦으면 놓친 note
+#[derive(Clone, Copy)]
+pub struct GradeWindows {
+    pub perfect: f32,
+    pub great: f32,
+    pub good: f32,
+    pub bad: f32,
+    pub miss: f32,
+}
+
+impl GradeWindows {
+    //offset은 입력 시각 - note timing(초). 음수면 빨리 누름
+    pub fn judge(&self, offset: f32) -> Option<JudgeAccuracy> {
+        match offset.abs() {
+            x if x <= self.perfect => Some(JudgeAccuracy::Perfect),
+            x if x <= self.great => Some(JudgeAccuracy::Great),
+            x if x <= self.good => Some(JudgeAccuracy::Good),
+            x if x <= self.bad => Some(JudgeAccuracy::Bad),
+            x if x <= self.miss => Some(JudgeAccuracy::Miss),
+            _ => None,
+        }
+    }
+
+    //입력 없이 miss window를 지나감
+    pub fn passed(&self, offset: f32) -> bool {
+        offset > self.miss
+    }
+}
+
+//누를 때와 long note를 뗄 때의 판정 범위
+#[derive(Clone, Copy)]
+pub struct JudgeWindows {
+    pub press: GradeWindows,
+    pub release: GradeWindows,
+}
+
+impl JudgeWindows {
+    //Standard의 Perfect, Great은 DJMAX V Respect 기준
+    pub fn from_preset(preset: JudgePreset) -> Self {
+        match preset {
+            JudgePreset::Lenient => JudgeWindows {
+                press: GradeWindows { perfect: 0.05, great: 0.11, good: 0.14, bad: 0.17, miss: 0.2 },
+                release: GradeWindows { perfect: 0.11, great: 0.14, good: 0.17, bad: 0.2, miss: 0.24 },
+            },
+            JudgePreset::Standard => JudgeWindows {
+                press: GradeWindows { perfect: 0.04167, great: 0.09, good: 0.12, bad: 0.15, miss: 0.18 },
+                release: GradeWindows { perfect: 0.09, great: 0.12, good: 0.15, bad: 0.18, miss: 0.21 },
+            },
+            JudgePreset::Strict => JudgeWindows {
+                press: GradeWindows { perfect: 0.03, great: 0.07, good: 0.1, bad: 0.13, miss: 0.15 },
+                release: GradeWindows { perfect: 0.07, great: 0.1, good: 0.12, bad: 0.14, miss: 0.16 },
+            },
+        }
+    }
+}
+
+impl Default for JudgeWindows {
+    fn default() -> Self {
+        JudgeWindows::from_preset(JudgePreset::Standard)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum HitKind {
+    Press,
+    Release,
+}
+
+//판정된 입력 하나. 시각은 모두 audio offset을 적용한 판정 시각 기준(ms)
+#[derive(Clone)]
+pub struct HitEvent {
+    pub lane: Press4Key,
+    pub kind: HitKind,
+    pub accuracy: JudgeAccuracy,
+    //판정한 note의 timing. Release면 release_timing
+    pub timing: f32,
+    pub input_time: f32,
+    //input_time - timing. 음수면 빨리 입력함
+    pub offset: f32,
+}
+
+//한 판 동안 판정된 입력을 순서대로 모음. 통계, 결과 그래프, replay에 사용
+#[derive(Default)]
+pub struct HitLog {
+    pub hits: Vec<HitEvent>,
+}
+
+impl HitLog {
+    //Miss를 제외한 누르기 판정의 offset(ms)
+    pub fn press_offsets(&self) -> Vec<f32> {
+        self.hits.iter()
+            .filter(|hit| hit.kind == HitKind::Press && hit.accuracy != JudgeAccuracy::Miss)
+            .map(|hit| hit.offset)
+            .collect()
+    }
+}
+
+//InGame에 들어갈 때 설정의 preset으로 판정 범위를 정함
+pub fn setup_judge_windows(
+    mut judge_windows: ResMut<JudgeWindows>,
+    settings: Res<Settings>,
+) {
+    *judge_windows = JudgeWindows::from_preset(settings.judge_preset);
+}
+
+pub fn reset_hit_log(mut hit_log: ResMut<HitLog>) {
+    hit_log.hits.clear();
+}
+
+pub fn record_hits(
+    mut events: EventReader<HitEvent>,
+    mut hit_log: ResMut<HitLog>,
+) {
+    hit_log.hits.extend(events.iter().cloned());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn judge(offset: f32) -> Option<JudgeAccuracy> {
+        JudgeWindows::from_preset(JudgePreset::Standard).press.judge(offset)
+    }
+
+    #[test]
+    fn window_boundaries_belong_to_the_better_grade() {
+        assert!(judge(0.) == Some(JudgeAccuracy::Perfect));
+        assert!(judge(-0.04167) == Some(JudgeAccuracy::Perfect));
+        assert!(judge(0.09) == Some(JudgeAccuracy::Great));
+        assert!(judge(0.12) == Some(JudgeAccuracy::Good));
+        assert!(judge(-0.15) == Some(JudgeAccuracy::Bad));
+        assert!(judge(0.18) == Some(JudgeAccuracy::Miss));
+    }
+
+    #[test]
+    fn early_and_late_are_judged_alike() {
+        for offset in [0.05, 0.1, 0.13, 0.16] {
+            assert!(judge(offset) == judge(-offset));
+        }
+        assert!(judge(-0.1) == Some(JudgeAccuracy::Good));
+    }
+
+    #[test]
+    fn outside_miss_window_is_ignored() {
+        assert!(judge(0.19).is_none());
+        assert!(judge(-0.19).is_none());
+    }
+
+    #[test]
+    fn passed_only_after_miss_window() {
+        let press = JudgeWindows::from_preset(JudgePreset::Standard).press;
+        assert!(!press.passed(0.18));
+        assert!(!press.passed(-1.));
+        assert!(press.passed(0.181));
+    }
+}
@@ -0,0 +1,290 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use serde::{Serialize, Deserialize};
+use crate::state::{GameState, take_key};
+use crate::notes::FontResource;
+use crate::settings::Settings;
+
+const TEXT_COLOR: Color = Color::rgba(0.9, 0.9, 0.9, 1.);
+const CONFLICT_COLOR: Color = Color::rgba(0.95, 0.3, 0.3, 1.);
+const BUTTON_COLOR: Color = Color::rgba(0.2, 0.2, 0.2, 0.8);
+const BUTTON_FOCUS_COLOR: Color = Color::rgba(0.9, 0.55, 0.2, 0.9);
+const BUTTON_CAPTURE_COLOR: Color = Color::rgba(0.3, 0.6, 0.9, 0.9);
+
+pub struct KeyBindingsPlugin;
+
+impl Plugin for KeyBindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Rebinding>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::KeyBindings)
+                .with_system(setup_key_bindings_menu)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::KeyBindings)
+                .with_system(binding_button_interaction)
+                .with_system(key_bindings_keyboard)
+                .with_system(update_binding_buttons)
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::KeyBindings)
+                .with_system(despawn_key_bindings_menu)
+            );
+    }
+}
+
+//lane별로 누르는 키와 gamepad 버튼. index는 Press4Key와 같음
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KeyBindings {
+    pub lanes: [KeyCode; 4],
+    //한 lane에 d-pad, face button, trigger를 함께 묶을 수 있음
+    #[serde(default = "default_gamepad_bindings")]
+    pub gamepad: [Vec<GamepadButtonType>; 4],
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            lanes: [KeyCode::Z, KeyCode::X, KeyCode::Period, KeyCode::Slash],
+            gamepad: default_gamepad_bindings(),
+        }
+    }
+}
+
+fn default_gamepad_bindings() -> [Vec<GamepadButtonType>; 4] {
+    [
+        vec![GamepadButtonType::DPadLeft, GamepadButtonType::LeftTrigger2],
+        vec![GamepadButtonType::DPadDown, GamepadButtonType::LeftTrigger],
+        vec![GamepadButtonType::South, GamepadButtonType::RightTrigger],
+        vec![GamepadButtonType::East, GamepadButtonType::RightTrigger2],
+    ]
+}
+
+impl KeyBindings {
+    //다른 lane과 같은 키나 gamepad 버튼을 쓰는 lane들의 index
+    pub fn conflicts(&self) -> Vec<usize> {
+        (0..self.lanes.len())
+            .filter(|&lane| {
+                (0..self.lanes.len()).any(|other| {
+                    other != lane && (
+                        self.lanes[other] == self.lanes[lane] ||
+                        self.gamepad[other].iter().any(|button| self.gamepad[lane].contains(button))
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+//rebinding 화면에서 focus된 lane과 다음 키를 기다리는 중인지 여부
+#[derive(Default)]
+pub struct Rebinding {
+    focus: usize,
+    capturing: bool,
+    message: String,
+}
+
+#[derive(Component)]
+pub struct KeyBindingsMenu;
+
+#[derive(Component)]
+pub struct BindingButton(usize);
+
+#[derive(Component)]
+pub struct BindingText(usize);
+
+#[derive(Component)]
+pub struct BindingMessage;
+
+pub fn setup_key_bindings_menu(
+    mut commands: Commands,
+    font_resource: Res<FontResource>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    *rebinding = Rebinding::default();
+    let text_style = |font_size: f32| TextStyle {
+        font: font_resource.font.clone(),
+        font_size,
+        color: TEXT_COLOR,
+    };
+
+    commands.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        parent.spawn_bundle(TextBundle {
+            style: Style {
+                margin: UiRect::all(Val::Px(30.)),
+                ..Default::default()
+            },
+            text: Text::from_section("Key Bindings", text_style(50.)),
+            ..Default::default()
+        });
+
+        for lane in 0..4 {
+            parent.spawn_bundle(ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(600.), Val::Px(50.)),
+                    margin: UiRect::all(Val::Px(6.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                color: BUTTON_COLOR.into(),
+                ..Default::default()
+            }).with_children(|parent| {
+                parent.spawn_bundle(TextBundle {
+                    text: Text::from_section("", text_style(26.)),
+                    focus_policy: FocusPolicy::Pass,
+                    ..Default::default()
+                }).insert(BindingText(lane));
+            }).insert(BindingButton(lane));
+        }
+
+        parent.spawn_bundle(TextBundle {
+            style: Style {
+                margin: UiRect::all(Val::Px(20.)),
+                ..Default::default()
+            },
+            text: Text::from_section("", text_style(20.)),
+            ..Default::default()
+        }).insert(BindingMessage);
+    }).insert(KeyBindingsMenu);
+}
+
+pub fn binding_button_interaction(
+    interaction_query: Query<(&Interaction, &BindingButton), Changed<Interaction>>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    if rebinding.capturing {
+        return;
+    }
+    for (interaction, button) in interaction_query.iter() {
+        match interaction {
+            Interaction::Clicked => {
+                rebinding.focus = button.0;
+                rebinding.capturing = true;
+            },
+            Interaction::Hovered => rebinding.focus = button.0,
+            Interaction::None => (),
+        }
+    }
+}
+
+pub fn key_bindings_keyboard(
+    mut key_input: ResMut<Input<KeyCode>>,
+    button_input: Res<Input<GamepadButton>>,
+    mut state: ResMut<State<GameState>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut settings: ResMut<Settings>,
+) {
+    let key_bindings = &mut settings.key_bindings;
+    if rebinding.capturing {
+        //Escape는 일시정지 키이므로 lane에 넣지 않고 취소로 사용
+        if let Some(key) = key_input.get_just_pressed().next().copied() {
+            take_key(&mut key_input, key);
+            if key != KeyCode::Escape {
+                let lane = rebinding.focus;
+                key_bindings.lanes[lane] = key;
+            }
+            rebinding.capturing = false;
+            rebinding.message.clear();
+        } else if let Some(button) = button_input.get_just_pressed().next() {
+            //gamepad 버튼은 키보드와 따로 저장되며 그 lane의 기존 버튼들을 대체
+            let lane = rebinding.focus;
+            key_bindings.gamepad[lane] = vec![button.1];
+            rebinding.capturing = false;
+            rebinding.message.clear();
+        }
+        return;
+    }
+
+    if take_key(&mut key_input, KeyCode::Escape) {
+        if key_bindings.conflicts().is_empty() {
+            settings.save();
+            state.pop().unwrap();
+        } else {
+            rebinding.message = "Two lanes share the same key".to_string();
+        }
+        return;
+    }
+    if key_input.just_pressed(KeyCode::Up) && rebinding.focus > 0 {
+        rebinding.focus -= 1;
+    }
+    if key_input.just_pressed(KeyCode::Down) && rebinding.focus + 1 < key_bindings.lanes.len() {
+        rebinding.focus += 1;
+    }
+    if take_key(&mut key_input, KeyCode::Return) {
+        rebinding.capturing = true;
+    }
+}
+
+pub fn update_binding_buttons(
+    mut button_query: Query<(&mut UiColor, &BindingButton)>,
+    mut text_query: Query<(&mut Text, &BindingText), Without<BindingMessage>>,
+    mut message_query: Query<&mut Text, With<BindingMessage>>,
+    rebinding: Res<Rebinding>,
+    settings: Res<Settings>,
+) {
+    let key_bindings = &settings.key_bindings;
+    let conflicts = key_bindings.conflicts();
+
+    for (mut color, button) in button_query.iter_mut() {
+        color.0 = if button.0 != rebinding.focus {
+            BUTTON_COLOR
+        } else if rebinding.capturing {
+            BUTTON_CAPTURE_COLOR
+        } else {
+            BUTTON_FOCUS_COLOR
+        };
+    }
+
+    for (mut text, binding_text) in text_query.iter_mut() {
+        let lane = binding_text.0;
+        text.sections[0].value = if rebinding.capturing && lane == rebinding.focus {
+            format!("Lane {}    press a key...", lane + 1)
+        } else {
+            let buttons: Vec<String> = key_bindings.gamepad[lane].iter()
+                .map(|button| format!("{:?}", button))
+                .collect();
+            format!("Lane {}    {:?}  /  {}", lane + 1, key_bindings.lanes[lane], buttons.join(", "))
+        };
+        text.sections[0].style.color = if conflicts.contains(&lane) {
+            CONFLICT_COLOR
+        } else {
+            TEXT_COLOR
+        };
+    }
+
+    for mut text in message_query.iter_mut() {
+        text.sections[0].value = if !rebinding.message.is_empty() {
+            rebinding.message.clone()
+        } else if rebinding.capturing {
+            "Press the new key or gamepad button (Esc to cancel)".to_string()
+        } else {
+            "Enter or click to rebind, Esc to save and go back".to_string()
+        };
+        text.sections[0].style.color = if conflicts.is_empty() {
+            TEXT_COLOR
+        } else {
+            CONFLICT_COLOR
+        };
+    }
+}
+
+pub fn despawn_key_bindings_menu(
+    mut commands: Commands,
+    query: Query<Entity, With<KeyBindingsMenu>>
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
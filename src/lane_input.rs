@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use bevy::input::InputSystem;
+use crate::settings::Settings;
+use crate::notes::Press4Key;
+
+pub struct LaneInputPlugin;
+
+impl Plugin for LaneInputPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<LaneInput>()
+            .add_system_to_stage(CoreStage::PreUpdate, update_lane_input.after(InputSystem));
+    }
+}
+
+//키보드와 gamepad 입력을 합친 lane별 입력 상태
+//여러 장치로 같은 lane을 누르고 있으면 모두 뗄 때까지 눌린 것으로 취급
+#[derive(Default)]
+pub struct LaneInput {
+    pressed: [bool; 4],
+    just_pressed: [bool; 4],
+    just_released: [bool; 4],
+}
+
+impl LaneInput {
+    pub fn pressed(&self, press_key: &Press4Key) -> bool {
+        self.pressed[press_key.clone() as usize]
+    }
+
+    pub fn just_pressed(&self, press_key: &Press4Key) -> bool {
+        self.just_pressed[press_key.clone() as usize]
+    }
+
+    pub fn just_released(&self, press_key: &Press4Key) -> bool {
+        self.just_released[press_key.clone() as usize]
+    }
+}
+
+pub fn update_lane_input(
+    mut lane_input: ResMut<LaneInput>,
+    key_input: Res<Input<KeyCode>>,
+    button_input: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    settings: Res<Settings>,
+) {
+    let key_bindings = &settings.key_bindings;
+    for lane in 0..4 {
+        let gamepad_pressed = gamepads.iter().any(|gamepad| {
+            key_bindings.gamepad[lane].iter()
+                .any(|button_type| button_input.pressed(GamepadButton(*gamepad, *button_type)))
+        });
+        let pressed = key_input.pressed(key_bindings.lanes[lane]) || gamepad_pressed;
+
+        lane_input.just_pressed[lane] = pressed && !lane_input.pressed[lane];
+        lane_input.just_released[lane] = !pressed && lane_input.pressed[lane];
+        lane_input.pressed[lane] = pressed;
+    }
+}
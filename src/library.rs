@@ -0,0 +1,254 @@
+use bevy::prelude::*;
+use std::path::Path;
+use crate::chart::{ChartAsset, ChartFile, ChartParseError};
+use crate::difficulty;
+use crate::select_menu::SongFocus;
+use bevy::asset::LoadState;
+
+//이 폴더 아래의 각 폴더가 한 곡
+const MUSIC_FOLDER: &str = "music";
+const AUDIO_EXTENSIONS: [&str; 4] = ["mp3", "ogg", "wav", "flac"];
+
+pub struct LibraryPlugin;
+
+impl Plugin for LibraryPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<SongLibrary>()
+            .add_system(update_song_library);
+    }
+}
+
+//한 곡의 난이도별 chart
+pub struct ChartInfo {
+    //asset 폴더 기준 경로
+    pub path: String,
+    pub handle: Handle<ChartAsset>,
+    //load 전에는 file 이름
+    pub difficulty: String,
+    pub level: u32,
+    //difficulty::calculate_difficulty로 계산한 값
+    pub stars: f32,
+    pub strain: Vec<f32>,
+}
+
+pub struct SongInfo {
+    pub name: String,
+    //곡 길이(초)
+    pub time: f32,
+    //가장 어려운 chart의 stars
+    pub difficult: f32,
+    //level 순으로 정렬
+    pub charts: Vec<ChartInfo>,
+    //chart가 load된 후에 채워짐
+    pub audio_path: Option<String>,
+    //미리듣기 시작 위치(초)
+    pub preview_time: f32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SongSort {
+    Name,
+    Difficulty,
+}
+
+pub struct SongLibrary {
+    pub songs: Vec<SongInfo>,
+    pub sort: SongSort,
+}
+
+impl SongLibrary {
+    pub fn sort_by(&mut self, sort: SongSort) {
+        self.sort = sort;
+        match sort {
+            SongSort::Name => self.songs.sort_by(|a, b| a.name.cmp(&b.name)),
+            SongSort::Difficulty => self.songs.sort_by(|a, b| a.difficult.partial_cmp(&b.difficult).unwrap()),
+        }
+        for song in self.songs.iter_mut() {
+            song.charts.sort_by(|a, b| a.level.cmp(&b.level).then_with(|| a.difficulty.cmp(&b.difficulty)));
+        }
+    }
+
+    //index인 focus가 정렬 후에도 같은 chart를 가리키도록 정렬 전 chart의 handle로 다시 찾음
+    pub fn sort_keeping_focus(&mut self, sort: SongSort, focus: &mut SongFocus) {
+        let focused = self.songs.get(focus.song)
+            .and_then(|song| song.charts.get(focus.difficulty).or_else(|| song.charts.first()))
+            .map(|chart_info| chart_info.handle.clone());
+        self.sort_by(sort);
+        if let Some((song, difficulty)) = focused.and_then(|handle| self.position(&handle)) {
+            focus.song = song;
+            focus.difficulty = difficulty;
+        }
+    }
+
+    //정렬이 바뀌어도 index 대신 handle로 chart를 찾음
+    pub fn find_chart(&self, handle: &Handle<ChartAsset>) -> Option<(&SongInfo, &ChartInfo)> {
+        let (song, difficulty) = self.position(handle)?;
+        let song = &self.songs[song];
+        Some((song, &song.charts[difficulty]))
+    }
+
+    //handle에 해당하는 chart의 (곡 index, 난이도 index)
+    pub fn position(&self, handle: &Handle<ChartAsset>) -> Option<(usize, usize)> {
+        self.songs.iter().enumerate().find_map(|(song_index, song)| {
+            song.charts.iter()
+                .position(|chart_info| chart_info.handle == *handle)
+                .map(|difficulty| (song_index, difficulty))
+        })
+    }
+}
+
+impl FromWorld for SongLibrary {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.get_resource::<AssetServer>().unwrap();
+        let asset_io = asset_server.asset_io();
+        let mut songs: Vec<SongInfo> = Vec::new();
+
+        let folders = match asset_io.read_directory(Path::new(MUSIC_FOLDER)) {
+            Ok(folders) => folders,
+            Err(error) => {
+                error!("could not read {}: {:?}", MUSIC_FOLDER, error);
+                return SongLibrary { songs, sort: SongSort::Name };
+            }
+        };
+
+        for folder in folders.filter(|path| asset_io.is_directory(path)) {
+            let files: Vec<_> = match asset_io.read_directory(&folder) {
+                Ok(files) => files.collect(),
+                Err(_) => continue,
+            };
+            //chart와 audio가 모두 있는 폴더만 곡으로 취급 (hit_sound 등은 제외)
+            let charts: Vec<ChartInfo> = files.iter()
+                .filter(|path| has_extension(path, &["chart"]))
+                .map(|path| {
+                    //AssetServer는 '/'로 구분된 경로를 사용
+                    let chart_path = path.to_string_lossy().replace('\\', "/");
+                    ChartInfo {
+                        handle: asset_server.load(chart_path.as_str()),
+                        path: chart_path,
+                        difficulty: path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+                        level: 0,
+                        stars: 0.,
+                        strain: Vec::new(),
+                    }
+                })
+                .collect();
+            if charts.is_empty() || !files.iter().any(|path| has_extension(path, &AUDIO_EXTENSIONS)) {
+                continue;
+            }
+
+            let name = folder.file_name().unwrap_or_default().to_string_lossy().to_string();
+            songs.push(SongInfo {
+                name,
+                time: 0.,
+                difficult: 0.,
+                charts,
+                audio_path: None,
+                preview_time: 0.,
+            });
+        }
+
+        let mut library = SongLibrary { songs, sort: SongSort::Name };
+        library.sort_by(SongSort::Name);
+        library
+    }
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map_or(false, |extension| extensions.contains(&extension))
+}
+
+//chart가 load되면 metadata로 SongInfo를 채움
+pub fn update_song_library(
+    mut library: ResMut<SongLibrary>,
+    mut focus: ResMut<SongFocus>,
+    mut events: EventReader<AssetEvent<ChartAsset>>,
+    charts: Res<Assets<ChartAsset>>,
+) {
+    let mut updated = false;
+    for event in events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+        let chart = match charts.get(handle).map(|asset| &asset.chart) {
+            Some(Ok(chart)) => chart,
+            _ => continue,
+        };
+
+        for song in library.songs.iter_mut() {
+            let chart_info = match song.charts.iter_mut().find(|chart_info| chart_info.handle == *handle) {
+                Some(chart_info) => chart_info,
+                None => continue,
+            };
+            chart_info.difficulty = chart.metadata.difficulty.clone();
+            chart_info.level = chart.metadata.level;
+            let rating = difficulty::calculate_difficulty(chart);
+            chart_info.stars = rating.stars;
+            chart_info.strain = rating.strain;
+            let chart_path = chart_info.path.clone();
+
+            //곡 정보는 같은 폴더의 chart끼리 공유
+            if !chart.metadata.title.is_empty() {
+                song.name = chart.metadata.title.clone();
+            }
+            let time = chart.notes.iter().map(|note| note.release_timing).max().unwrap_or(0) as f32 / 1000.;
+            song.time = song.time.max(time);
+            song.audio_path = Some(relative_path(&chart_path, &chart.metadata.audio));
+            song.preview_time = chart.metadata.preview_time as f32 / 1000.;
+            song.difficult = song.charts.iter().map(|chart_info| chart_info.stars).fold(0., f32::max);
+            updated = true;
+        }
+    }
+
+    //이름이나 난이도가 바뀌었을 수 있으므로 다시 정렬
+    if updated {
+        let sort = library.sort;
+        library.sort_keeping_focus(sort, &mut focus);
+    }
+}
+
+//게임에서 플레이할 곡과 난이도. select menu에서 곡을 고르면 insert됨
+//library가 다시 정렬될 수 있으므로 index 대신 chart 경로와 handle로 가리킴
+pub struct SelectedSong {
+    pub chart_path: String,
+    pub chart: Handle<ChartAsset>,
+}
+
+impl SelectedSong {
+    pub fn new(chart_info: &ChartInfo) -> Self {
+        SelectedSong {
+            chart_path: chart_info.path.clone(),
+            chart: chart_info.handle.clone(),
+        }
+    }
+
+    //아직 loading 중이면 None
+    pub fn get<'a>(
+        &self,
+        asset_server: &AssetServer,
+        charts: &'a Assets<ChartAsset>,
+    ) -> Option<Result<&'a ChartFile, Vec<ChartParseError>>> {
+        if let Some(asset) = charts.get(&self.chart) {
+            return Some(asset.chart.as_ref().map_err(|errors| errors.clone()));
+        }
+        match asset_server.get_load_state(&self.chart) {
+            LoadState::Failed => Some(Err(vec![ChartParseError::new(0, 0, format!("could not load {}", self.chart_path))])),
+            _ => None,
+        }
+    }
+
+    pub fn audio_path(&self, chart: &ChartFile) -> String {
+        relative_path(&self.chart_path, &chart.metadata.audio)
+    }
+}
+
+//chart_path와 같은 폴더에 있는 file의 경로
+pub fn relative_path(chart_path: &str, file: &str) -> String {
+    match chart_path.rsplit_once('/') {
+        Some((folder, _)) => format!("{}/{}", folder, file),
+        None => file.to_string(),
+    }
+}
@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use bevy::asset::AssetServerSettings;
 use bevy_inspector_egui::WorldInspectorPlugin;
 use bevy_kira_audio::AudioPlugin;
 //use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
@@ -8,28 +9,66 @@ mod notes;
 mod audio;
 mod state;
 mod select_menu;
+mod chart;
+mod chart_error;
+mod library;
+mod difficulty;
+mod timing;
+mod scroll;
+mod result;
+mod start_menu;
+mod settings_menu;
+mod records;
+mod key_bindings;
+mod settings;
+mod calibration;
+mod lane_input;
+mod judge;
+mod timing_meter;
+mod score;
 
 
 fn main() {
     let mut app = App::new();    
+    let (settings, settings_error) = settings::Settings::load();
     app.insert_resource(Msaa { samples: 4});
     app.insert_resource(WindowDescriptor {
         title: "rhythme 0.1.0".to_string(),
-        width: 1000.0,
-        height: 1000.0,
+        width: settings.window_width,
+        height: settings.window_height,
+        ..Default::default()
+    });
+    app.insert_resource(settings);
+    app.insert_resource(settings_error);
+    //chart 파일을 수정하면 게임 중에 바로 다시 불러옴
+    app.insert_resource(AssetServerSettings {
+        watch_for_changes: true,
         ..Default::default()
     });
     app.add_plugins(DefaultPlugins);
     app.add_plugin(AudioPlugin);
+    app.add_plugin(chart::ChartPlugin);
+    app.add_plugin(library::LibraryPlugin);
     app.add_plugin(WorldInspectorPlugin::new());
     //app.add_plugin(bevy_framepace::FramepacePlugin::default());
     app.add_startup_system(camera_setup);
+    app.add_startup_system(settings::report_settings_error);
     //app.add_startup_system(frame_limit);
 
-    app.add_state(GameState::SelectMenu);
-    //app.add_state(GameState::SelectMenu);
+    app.add_state(GameState::StartMenu);
+    app.add_plugin(start_menu::StartMenuPlugin);
+    app.add_plugin(settings_menu::SettingsMenuPlugin);
+    app.add_plugin(records::RecordsPlugin);
+    app.add_plugin(key_bindings::KeyBindingsPlugin);
+    app.add_plugin(lane_input::LaneInputPlugin);
+    app.add_plugin(calibration::CalibrationPlugin);
     app.add_plugin(select_menu::SelectMenuPlugin);
     app.add_plugin(notes::NotePlugin);
+    app.add_plugin(judge::JudgePlugin);
+    app.add_plugin(timing_meter::TimingMeterPlugin);
+    app.add_plugin(score::ScorePlugin);
+    app.add_plugin(chart_error::ChartErrorPlugin);
+    app.add_plugin(result::ResultPlugin);
     app.add_plugin(audio::GameAudioPlugin);
     //app.add_system(notes::print_keyboard_event_system); // for debug
     //app.add_plugin(FrameTimeDiagnosticsPlugin::default()); // for debug
@@ -1,19 +1,38 @@
 use bevy::prelude::*;
 use std::collections::VecDeque;
-use std::io::prelude::*;
-use std::io::BufReader;
-use std::fs::File;
 use core::time;
 use crate::state::GameState;
+use crate::chart::{ChartAsset, ChartFile};
+use crate::timing::TimingMap;
+use crate::scroll::ScrollMap;
+use crate::library::SelectedSong;
+use crate::result::PlayResult;
+use crate::lane_input::LaneInput;
+use crate::judge::{JudgeWindows, HitEvent, HitKind, HitLog};
+use crate::score::{self, JudgementTotal};
+use crate::settings::{Settings, DEFAULT_NOTE_SPEED};
+use crate::audio::{MainTrackChannel, MainTrackInstance};
+use bevy_kira_audio::{AudioChannel, AudioControl, PlaybackState};
 
 const STANDARD_NOTE_SPEED: f32 = 100.;
 const HOLD_TIME: f32 = 3000.;
 const MAX_MUSIC_LENGTH: f32 = 600000.;
-const JUDGE_LINE: f32 = -250.;
-const FIRST_KEY: KeyCode = KeyCode::Z;
-const SECOND_KEY: KeyCode = KeyCode::X;
-const THIRD_KEY: KeyCode = KeyCode::Period;
-const FOURTH_KEY: KeyCode = KeyCode::Slash;
+pub const JUDGE_LINE: f32 = -250.;
+//마지막 note가 판정된 후 Result로 넘어가기까지 대기하는 시간(초)
+const RESULT_DELAY: f32 = 2.;
+//scroll speed를 바꾸는 키
+const SCROLL_SPEED_DOWN_KEY: KeyCode = KeyCode::F3;
+const SCROLL_SPEED_UP_KEY: KeyCode = KeyCode::F4;
+//MusicTimer와 실제 재생 위치의 차이(초)가 이보다 크면 바로 맞추고, 작으면 매 frame CLOCK_SYNC_RATE 비율만큼 줄임
+const MAX_CLOCK_DRIFT: f32 = 0.1;
+const CLOCK_SYNC_RATE: f32 = 0.1;
+//네 lane을 가로지르는 마디선
+const BAR_LINE_WIDTH: f32 = 404.;
+const BAR_LINE_COLOR: Color = Color::rgba(0.98, 0.98, 0.98, 0.3);
+//판정 sprite 중심에서 EARLY/LATE 표시까지의 x 거리
+const EARLY_LATE_X: f32 = 270.;
+const EARLY_COLOR: Color = Color::rgba(0.4, 0.7, 1., 0.9);
+const LATE_COLOR: Color = Color::rgba(1., 0.45, 0.35, 0.9);
 
 pub struct FontResource {
     pub font: Handle<Font>,
@@ -70,6 +89,7 @@ impl FromWorld for NoteResource {
 pub struct JudgeResource {
     perfect: Handle<Image>,
     great: Handle<Image>,
+    good: Handle<Image>,
     miss: Handle<Image>,
     bad: Handle<Image>,
 }
@@ -83,6 +103,7 @@ impl FromWorld for JudgeResource {
         let judge_resource = JudgeResource {
             perfect: asset_server.load("image/perfect.png"),
             great: asset_server.load("image/great.png"),
+            good: asset_server.load("image/good.png"),
             miss: asset_server.load("image/miss.png"),
             bad: asset_server.load("image/bad.png"),
         };
@@ -144,13 +165,13 @@ pub enum NoteType{
 
 #[derive(Component, Clone)]
 pub struct Note {
-    note_type: NoteType,
-    press_key: Press4Key,
-    timing: usize,
-    release_timing: usize,
+    pub note_type: NoteType,
+    pub press_key: Press4Key,
+    pub timing: usize,
+    pub release_timing: usize,
     pushed: bool,
     missed: bool,
-    speed: f32,
+    pub speed: f32,
     judge: JudgeAccuracy,
 }
 
@@ -162,6 +183,12 @@ pub struct Chart {
     notes: VecDeque<Note>
 }
 
+//timing(ms)에 판정선을 지나는 마디선
+#[derive(Component)]
+pub struct BarLine {
+    timing: f32,
+}
+
 #[derive(Component)]
 pub struct MusicTimer {
     pub timer: Timer,
@@ -177,12 +204,43 @@ pub struct PausedText;
 #[derive(Component)]
 pub struct TimerText;
 
+//InGame을 나갈 때 despawn_game에서 정리할 entity
 #[derive(Component)]
+pub struct InGameEntity;
+
+#[derive(Component, Default)]
 pub struct Scoreboard {
-    perfect: usize,
-    great: usize,
-    miss: usize,
-    bad: usize,
+    pub perfect: usize,
+    pub great: usize,
+    pub good: usize,
+    pub miss: usize,
+    pub bad: usize,
+    //판정 단위의 combo. 화면, 결과, 점수 모두 이 값을 사용
+    pub combo: usize,
+    pub max_combo: usize,
+}
+
+impl Scoreboard {
+    fn count(&mut self, accuracy: JudgeAccuracy) {
+        match accuracy {
+            JudgeAccuracy::Perfect => self.perfect += 1,
+            JudgeAccuracy::Great => self.great += 1,
+            JudgeAccuracy::Good => self.good += 1,
+            JudgeAccuracy::Bad => self.bad += 1,
+            JudgeAccuracy::Miss => self.miss += 1,
+            JudgeAccuracy::None => return,
+        }
+        if accuracy == JudgeAccuracy::Miss {
+            self.combo = 0;
+        } else {
+            self.combo += 1;
+        }
+        self.max_combo = self.max_combo.max(self.combo);
+    }
+
+    pub fn judged(&self) -> usize {
+        self.perfect + self.great + self.good + self.bad + self.miss
+    }
 }
 
 #[derive(Component)]
@@ -207,25 +265,23 @@ pub struct ThirdLane;
 #[derive(Component)]
 pub struct FourthLane;
 
-#[derive(Component)]
-pub struct Combo(u32);
-
 //차후에 combo animation 구현 후 삭제
 #[derive(Component)]
 pub struct ComboText;
 
 #[derive(Component)]
-pub struct TotalAccuracy(f32);
+pub struct TotalAccuracy(pub f32);
 
 #[derive(Component)]
 pub struct AccuracyText;
 
-pub struct EventCombo {
-    judge: JudgeAccuracy,
-}
+#[derive(Component)]
+pub struct ScrollSpeedText;
 
 pub struct EventAnimation {
     judge: JudgeAccuracy,
+    //입력 시각 - note timing(ms). 입력 없이 판정되면 None
+    offset: Option<f32>,
 }
 
 pub struct KeySound1;
@@ -242,6 +298,7 @@ impl Plugin for NotePlugin {
             .init_resource::<NoteResource>()
             .init_resource::<FontResource>()
             .init_resource::<JudgeResource>()
+            .init_resource::<ScrollMap>()
             //.init_resource::<NumberResource>()
 
             .add_event::<EventAnimation>()
@@ -249,7 +306,6 @@ impl Plugin for NotePlugin {
             .add_event::<KeySound2>()
             .add_event::<KeySound3>()
             .add_event::<KeySound4>()
-            .add_event::<EventCombo>()
 
             .add_system_set(
                 SystemSet::on_enter(GameState::InGame)
@@ -258,6 +314,7 @@ impl Plugin for NotePlugin {
                 .with_system(open_chart)
                 .with_system(setup_accuracy)
                 .with_system(setup_combo)
+                .with_system(setup_scroll_speed_text)
                 .with_system(spawn_long_note_timer)
             )
             .add_system_set(
@@ -277,7 +334,8 @@ impl Plugin for NotePlugin {
                 .with_system(despawn_note_2)
                 .with_system(despawn_note_3)
     
-                .with_system(move_note)
+                .with_system(move_note.after(game_ticking))
+                .with_system(move_bar_line.after(game_ticking))
     
                 .with_system(spawn_keyboard_backlight)
                 .with_system(despawn_keyboard_backlight)
@@ -288,21 +346,33 @@ impl Plugin for NotePlugin {
                 .with_system(update_combo_effect)
     
                 .with_system(pause_game)
+                .with_system(change_scroll_speed)
+                .with_system(reload_chart)
+                .with_system(check_game_end)
                 /* Debug Only */
                 .with_system(print_keyboard_event_system)
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::InGame)
+                .with_system(despawn_game)
+                .with_system(save_scroll_speed)
             );
 
            
     }
 }
 
+pub fn save_scroll_speed(settings: Res<Settings>) {
+    settings.save();
+}
+
 pub fn spawn_long_note_timer(
     mut commands: Commands,
 ) {
-    commands.spawn().insert(LongNoteTimer{timer: Timer::from_seconds(0.25, true)}).insert(FirstLane);
-    commands.spawn().insert(LongNoteTimer{timer: Timer::from_seconds(0.25, true)}).insert(SecondLane);
-    commands.spawn().insert(LongNoteTimer{timer: Timer::from_seconds(0.25, true)}).insert(ThirdLane);
-    commands.spawn().insert(LongNoteTimer{timer: Timer::from_seconds(0.25, true)}).insert(FourthLane);
+    commands.spawn().insert(LongNoteTimer{timer: Timer::from_seconds(0.25, true)}).insert(FirstLane).insert(InGameEntity);
+    commands.spawn().insert(LongNoteTimer{timer: Timer::from_seconds(0.25, true)}).insert(SecondLane).insert(InGameEntity);
+    commands.spawn().insert(LongNoteTimer{timer: Timer::from_seconds(0.25, true)}).insert(ThirdLane).insert(InGameEntity);
+    commands.spawn().insert(LongNoteTimer{timer: Timer::from_seconds(0.25, true)}).insert(FourthLane).insert(InGameEntity);
 }
 
 pub fn spawn_background(
@@ -313,13 +383,13 @@ pub fn spawn_background(
         texture: materials.background.clone(),
         transform: Transform::from_translation(Vec3::new(0., 0., 0.)),
         ..Default::default()
-    });
+    }).insert(InGameEntity);
 
     commands.spawn_bundle(SpriteBundle {
         texture: materials.judge.clone(),
         transform: Transform::from_translation(Vec3::new(0., JUDGE_LINE, 2.)),
         ..Default::default()
-    });
+    }).insert(InGameEntity);
 
     for i in -2..3 {
         commands.spawn_bundle(SpriteBundle {
@@ -327,7 +397,7 @@ pub fn spawn_background(
             texture: materials.line.clone(),
             transform: Transform::from_translation(Vec3::new(i as f32 * 101., 0., 1.)),
             ..Default::default()
-        });
+        }).insert(InGameEntity);
     }
 }
 
@@ -335,7 +405,9 @@ pub fn game_ticking(
     mut commands: Commands,
     time: Res<Time>,
     mut timer: Query<(Entity, &mut MusicTimer, Without<Hold>)>,
-    mut hold_time: Query<(Entity, &mut MusicTimer, With<Hold>)>
+    mut hold_time: Query<(Entity, &mut MusicTimer, With<Hold>)>,
+    audio_channel: Res<AudioChannel<MainTrackChannel>>,
+    main_track: Res<MainTrackInstance>,
 ) {
     for (entity, mut music_time, _hold) in hold_time.iter_mut() {
         music_time.timer.tick(time.delta());
@@ -353,15 +425,42 @@ pub fn game_ticking(
     for (_entity, mut music_timer, _dummy) in timer.iter_mut() {    
         if !music_timer.timer.paused() {
             music_timer.timer.tick(time.delta());
+            sync_music_timer(&mut music_timer, &audio_channel, &main_track, time.delta_seconds());
         }
     }
 }
 
+//decoding 지연이나 frame hitch로 MusicTimer가 음악과 어긋나지 않도록 AudioInstance의 재생 위치에 맞춤
+//위치 값은 audio callback 단위로 갱신되어 jitter가 있으므로 작은 차이는 조금씩 줄여나감
+fn sync_music_timer(
+    music_timer: &mut MusicTimer,
+    audio_channel: &AudioChannel<MainTrackChannel>,
+    main_track: &MainTrackInstance,
+    delta: f32,
+) {
+    let position = match main_track.0.as_ref().map(|handle| audio_channel.state(handle)) {
+        Some(PlaybackState::Playing { position }) => position as f32,
+        _ => return,
+    };
+
+    let elapsed = music_timer.timer.elapsed_secs();
+    let drift = position - elapsed;
+    let correction = if drift.abs() > MAX_CLOCK_DRIFT {
+        drift
+    } else {
+        //시간이 거꾸로 흐르지 않도록 이번 frame에 흐른 시간 이상 되돌리지 않음
+        (drift * CLOCK_SYNC_RATE).max(-delta)
+    };
+    music_timer.timer.set_elapsed(time::Duration::from_secs_f32((elapsed + correction).max(0.)));
+}
+
 //-151.5
 pub fn spawn_note_0(
     mut commands: Commands,
     materials: Res<NoteResource>,
     mut query_entity: Query<(Entity, &mut Chart, With<FirstLane>)>,
+    settings: Res<Settings>,
+    scroll: Res<ScrollMap>,
     timer: Query<(&MusicTimer, Without<Hold>)>,
 ) {
 
@@ -372,7 +471,7 @@ pub fn spawn_note_0(
             commands.entity(entity).despawn();
             return;
         }
-        spawn_note(&mut commands, material, &mut query, -151.5, music_timer);
+        spawn_note(&mut commands, material, &mut query, -151.5, music_timer, &settings, &scroll);
     }
 
 }
@@ -382,6 +481,8 @@ pub fn spawn_note_1(
     mut commands: Commands,
     materials: Res<NoteResource>,
     mut query_entity: Query<(Entity, &mut Chart, With<SecondLane>)>,
+    settings: Res<Settings>,
+    scroll: Res<ScrollMap>,
     timer: Query<(&MusicTimer, Without<Hold>)>, 
 ) {
     for (entity, mut query, _lane) in query_entity.iter_mut() {
@@ -391,7 +492,7 @@ pub fn spawn_note_1(
             commands.entity(entity).despawn();
             return;
         }
-        spawn_note(&mut commands, material, &mut query, -50.5, music_timer);
+        spawn_note(&mut commands, material, &mut query, -50.5, music_timer, &settings, &scroll);
     }
 }
 
@@ -400,6 +501,8 @@ pub fn spawn_note_2(
     mut commands: Commands,
     materials: Res<NoteResource>,
     mut query_entity: Query<(Entity, &mut Chart, With<ThirdLane>)>,
+    settings: Res<Settings>,
+    scroll: Res<ScrollMap>,
     timer: Query<(&MusicTimer, Without<Hold>)>,
 ) {
     for (entity, mut query, _lane) in query_entity.iter_mut() {
@@ -409,7 +512,7 @@ pub fn spawn_note_2(
             commands.entity(entity).despawn();
             return;
         }
-        spawn_note(&mut commands, material, &mut query, 50.5, music_timer);
+        spawn_note(&mut commands, material, &mut query, 50.5, music_timer, &settings, &scroll);
     }
 }
 
@@ -418,6 +521,8 @@ pub fn spawn_note_3(
     mut commands: Commands,
     materials: Res<NoteResource>,
     mut query_entity: Query<(Entity, &mut Chart, With<FourthLane>)>,
+    settings: Res<Settings>,
+    scroll: Res<ScrollMap>,
     timer: Query<(&MusicTimer, Without<Hold>)>,
 ) {
     for (entity, mut query, _lane) in query_entity.iter_mut() {
@@ -427,7 +532,7 @@ pub fn spawn_note_3(
             commands.entity(entity).despawn();
             return;
         }
-        spawn_note(&mut commands, material, &mut query, 151.5, music_timer);
+        spawn_note(&mut commands, material, &mut query, 151.5, music_timer, &settings, &scroll);
     }
 }
 
@@ -437,19 +542,22 @@ fn spawn_note(
     chart: &mut Chart,
     position_x: f32,
     timer: &MusicTimer,
+    settings: &Settings,
+    scroll: &ScrollMap,
 ) {
     // -350 : Judgement line. (STANDARD_NOTE_SPEED * note.speed) = 1초에 움직이는 거리. 즉 생성할때 chart.notes[0].timing / 1000초만큼 이동해야 판정선에 닿도록 함
     // y가 530보다 큰 것은 생성하지 않음
-    let position_y: f32 = JUDGE_LINE + (((chart.notes[0].timing as f32) / 1000.) - timer.timer.elapsed_secs())
-     * (STANDARD_NOTE_SPEED * chart.notes[0].speed);
+    let speed = note_speed(&chart.notes[0], settings.scroll_speed);
+    let timing = chart.notes[0].timing as f32 / 1000.;
+    let position_y: f32 = JUDGE_LINE + scroll_distance(scroll, settings.draw_time(timer.timer.elapsed_secs()), timing) * speed;
 
     if position_y <= 530.{
         //println!("Note spawned");
         //println!("{}", position_y);
 
         if chart.notes[0].note_type == NoteType::Long {
-            let release_position: f32 = position_y + ((chart.notes[0].release_timing - chart.notes[0].timing) as f32 / 1000.)
-             * (STANDARD_NOTE_SPEED * chart.notes[0].speed);
+            let release_position: f32 = position_y + scroll_distance(scroll, timing, chart.notes[0].release_timing as f32 / 1000.)
+             * speed;
             let mut position = Transform::from_translation(Vec3::new(position_x, position_y + (release_position - position_y) / 2., 3.));
             let scale = 1. + (1. / 30. * (release_position - position_y));
             let scale = Scale(scale);
@@ -458,7 +566,7 @@ fn spawn_note(
                 texture: material,
                 transform: position,
                 ..Default::default()
-            }).insert(Note {
+            }).insert(InGameEntity).insert(Note {
                 note_type: chart.notes[0].note_type.clone(),
                 press_key: chart.notes[0].press_key.clone(),
                 release_timing: chart.notes[0].release_timing,
@@ -474,7 +582,7 @@ fn spawn_note(
                 texture: material,
                 transform: position,
                 ..Default::default()
-            }).insert(Note {
+            }).insert(InGameEntity).insert(Note {
                 note_type: chart.notes[0].note_type.clone(),
                 press_key: chart.notes[0].press_key.clone(),
                 release_timing: chart.notes[0].release_timing,
@@ -490,146 +598,188 @@ fn spawn_note(
     }
 }
 
+//매 frame 현재 곡 시간과 note.timing으로 위치를 새로 계산하여 frame hitch나 일시정지로 오차가 쌓이지 않게 함
 pub fn move_note(
     mut query_note: Query<(Entity, &Note, &mut Transform)>,
     timer: Query<(Entity, &MusicTimer, Without<Hold>)>,
-    time: Res<Time>
+    settings: Res<Settings>,
+    scroll: Res<ScrollMap>,
 ) {
     let (_entity, music_timer, _dummy) = timer.single();
+    let elapsed = settings.draw_time(music_timer.timer.elapsed_secs());
     for (_entity, note, mut transform) in query_note.iter_mut() {
-        if !music_timer.timer.paused() && music_timer.timer.elapsed_secs() > 0. {
-            transform.translation.y -= time.delta_seconds() * STANDARD_NOTE_SPEED * note.speed;
+        let (position_y, scale) = note_placement(note, elapsed, note_speed(note, settings.scroll_speed), &scroll);
+        transform.translation.y = position_y;
+        transform.scale.y = scale;
+    }
+}
+
+//from초에서 to초까지 SV 배율을 적분한 scroll 거리(초 단위). SV가 없으면 to - from
+fn scroll_distance(scroll: &ScrollMap, from: f32, to: f32) -> f32 {
+    (scroll.position(to * 1000.) - scroll.position(from * 1000.)) / 1000.
+}
+
+pub fn move_bar_line(
+    mut commands: Commands,
+    mut query: Query<(Entity, &BarLine, &mut Transform)>,
+    timer: Query<&MusicTimer, Without<Hold>>,
+    settings: Res<Settings>,
+    scroll: Res<ScrollMap>,
+) {
+    let elapsed = settings.draw_time(timer.single().timer.elapsed_secs());
+    let speed = STANDARD_NOTE_SPEED * settings.note_speed * settings.scroll_speed;
+    for (entity, bar_line, mut transform) in query.iter_mut() {
+        transform.translation.y = JUDGE_LINE + scroll_distance(&scroll, elapsed, bar_line.timing / 1000.) * speed;
+        if bar_line.timing / 1000. < elapsed {
+            commands.entity(entity).despawn();
         }
     }
-    
+}
+
+//note가 1초에 움직이는 거리. scroll speed는 화면상의 간격만 바꾸고 판정 timing에는 영향이 없음
+fn note_speed(note: &Note, scroll_speed: f32) -> f32 {
+    STANDARD_NOTE_SPEED * note.speed * scroll_speed
+}
+
+//elapsed(초) 시점에 note가 있어야 할 y 위치와 y scale
+//누르고 있는 long note는 판정선 아래로 내려가지 않고 남은 길이만큼 줄어듦
+fn note_placement(note: &Note, elapsed: f32, speed: f32, scroll: &ScrollMap) -> (f32, f32) {
+    let mut head = JUDGE_LINE + scroll_distance(scroll, elapsed, note.timing as f32 / 1000.) * speed;
+    if note.note_type == NoteType::Short {
+        return (head, 1.);
+    }
+    let release = JUDGE_LINE + scroll_distance(scroll, elapsed, note.release_timing as f32 / 1000.) * speed;
+    if note.pushed {
+        head = head.max(JUDGE_LINE).min(release);
+    }
+    (head + (release - head) / 2., 1. + (1. / 30. * (release - head)))
 }
 
 fn despawn_note(
     commands: &mut Commands,
-    input_key: Input<KeyCode>,
-    key_code: KeyCode,
+    lane_input: &LaneInput,
     mut note: &mut Note,
     time: &Res<Time>,
     music_timer: &MusicTimer,
     mut score: &mut Scoreboard,
     entity: Entity,
-    mut transform: &mut Transform,
     mut lane_query: &mut LongNoteTimer,     //타이머 4개를 Lane별로 사용하여 만드는게나을듯?
-) -> (bool, JudgeAccuracy) {
-    //Judgement : Perfect 0.04167sec (DJMAX V Respect)
-    //            Great   0.09000sec
-   
+    settings: &Settings,
+    judge_windows: &JudgeWindows,
+    hit_events: &mut EventWriter<HitEvent>,
+) -> (bool, JudgeAccuracy, Option<f32>) {
+    //반환값의 offset(ms)은 입력으로 판정했을 때만 있음
+    let judge_time = settings.judge_time(music_timer.timer.elapsed_secs());
+    //입력 시각 - note timing(초). 음수면 빨리 누름
+    let offset = judge_time - note.timing as f32 / 1000.;
+    let release_offset = judge_time - note.release_timing as f32 / 1000.;
     if !music_timer.timer.paused() {
-        if input_key.just_pressed(key_code) {
-            //println!("current timer: {}", music_timer.timer.elapsed_secs());
-            if note.note_type == NoteType::Short {
-                if (note.timing as f32 / 1000. + 0.04167 >= music_timer.timer.elapsed_secs()) && (note.timing as f32 / 1000. - 0.04167 <= music_timer.timer.elapsed_secs()) {
-                    //println!("note timing : {}", note.timing as f32 / 1000.);
-                    commands.entity(entity).despawn();
-                    score.perfect += 1;
-                    //println!("perfect {}", note.timing as f32 / 1000.);
-                    note.judge = JudgeAccuracy::Perfect;
-                    return (true, JudgeAccuracy::Perfect);
-                } else if (note.timing as f32 / 1000. + 0.09  >= music_timer.timer.elapsed_secs()) && (note.timing as f32 / 1000. - 0.09 <= music_timer.timer.elapsed_secs()) {
-                    //println!("note timing : {}", note.timing as f32 / 1000.);
+        if lane_input.just_pressed(&note.press_key) && !note.pushed && !note.missed {
+            //miss window보다 빨리 누르면 판정하지 않음
+            if let Some(accuracy) = judge_windows.press.judge(offset) {
+                score.count(accuracy);
+                hit_events.send(HitEvent {
+                    lane: note.press_key.clone(),
+                    kind: HitKind::Press,
+                    accuracy,
+                    timing: note.timing as f32,
+                    input_time: judge_time * 1000.,
+                    offset: offset * 1000.,
+                });
+                if note.note_type == NoteType::Short {
                     commands.entity(entity).despawn();
-                    score.great += 1;
-                    //println!("great {}", note.timing as f32 / 1000.);
-                    note.judge = JudgeAccuracy::Great;
-                    return (true, JudgeAccuracy::Great);
-                }
-            } else if (note.note_type == NoteType::Long) && (note.pushed == false) {
-                if (note.timing as f32 / 1000. + 0.04167 >= music_timer.timer.elapsed_secs()) && (note.timing as f32 / 1000. - 0.04167 <= music_timer.timer.elapsed_secs()) {
-                    score.perfect += 1;
-                    lane_query.timer.set_elapsed(time::Duration::from_secs_f32(0.));
-                    note.pushed = true;
-                    note.judge = JudgeAccuracy::Perfect;
-                    return (true, JudgeAccuracy::Perfect);
-                } else if (note.timing as f32 / 1000. + 0.09  >= music_timer.timer.elapsed_secs()) && (note.timing as f32 / 1000. - 0.09 <= music_timer.timer.elapsed_secs()) {
-                    score.great += 1;
+                } else if accuracy == JudgeAccuracy::Miss {
+                    note.missed = true;
+                } else {
                     lane_query.timer.set_elapsed(time::Duration::from_secs_f32(0.));
                     note.pushed = true;
-                    note.judge = JudgeAccuracy::Great;
-                    return (true, JudgeAccuracy::Great);
-                } else if note.timing as f32 / 1000. + 0.09 < music_timer.timer.elapsed_secs() {
-                    score.miss += 1;
-                    note.missed = true;
-                    note.judge = JudgeAccuracy::Bad;
-                    println!("start miss {}", music_timer.timer.elapsed_secs());
-                    return (true, JudgeAccuracy::Miss);
                 }
+                note.judge = accuracy;
+                return (true, accuracy, Some(offset * 1000.));
             }
         }
-        //아예 안누르면 Short이든, Long이든 삭제. Short의 경우 release_timing과 timing이 동일
 
-        if input_key.just_released(key_code) && note.note_type == NoteType::Long {
-            if (note.release_timing as f32 / 1000. + 0.09 >= music_timer.timer.elapsed_secs()) && (note.release_timing as f32 / 1000. - 0.09 <= music_timer.timer.elapsed_secs()) {
-                score.perfect += 1;
+        if note.note_type == NoteType::Long && note.pushed && !note.missed {
+            if lane_input.just_released(&note.press_key) {
+                //release window보다 빨리 떼면 Miss
+                let accuracy = judge_windows.release.judge(release_offset).unwrap_or(JudgeAccuracy::Miss);
+                score.count(accuracy);
+                hit_events.send(HitEvent {
+                    lane: note.press_key.clone(),
+                    kind: HitKind::Release,
+                    accuracy,
+                    timing: note.release_timing as f32,
+                    input_time: judge_time * 1000.,
+                    offset: release_offset * 1000.,
+                });
                 commands.entity(entity).despawn();
-                return (true, JudgeAccuracy::Perfect);
-            } else if (note.missed == false) && (note.timing as f32 / 1000. + 0.09 < music_timer.timer.elapsed_secs()) && (note.release_timing as f32 / 1000. - 0.09 > music_timer.timer.elapsed_secs()){
-                note.judge = JudgeAccuracy::Bad;
-                score.miss += 1;
-                note.missed = true;
-                return (true, JudgeAccuracy::Miss);
+                return (true, accuracy, Some(release_offset * 1000.));
             }
-        }
 
-        if note.note_type == NoteType::Long {
             //계속 누르고 있을때의 동작
-            if input_key.pressed(key_code) && (note.timing as f32 / 1000. <= music_timer.timer.elapsed_secs()) && 
-                (note.release_timing as f32 / 1000. >= music_timer.timer.elapsed_secs()) {
+            if lane_input.pressed(&note.press_key) && (note.timing as f32 / 1000. <= judge_time) && 
+                (note.release_timing as f32 / 1000. >= judge_time) {
                 //println!("timer: {}", lane_query.timer.elapsed_secs());
-                //TODO: 노트가 삭제되는 과정에 대한 개선 필요
+                //줄어드는 모양은 move_note에서 note.pushed를 보고 그림
                 lane_query.timer.tick(time.delta());
-                
-                let position_diff = time.delta_seconds() * STANDARD_NOTE_SPEED * note.speed;
-                transform.translation.y += position_diff / 2.;
-                transform.scale.y -= position_diff / 30.;
 
                 if lane_query.timer.just_finished() {
-                    return (true, note.judge);
+                    return (true, note.judge, None);
                 }
-                return (true, JudgeAccuracy::None);
+                return (true, JudgeAccuracy::None, None);
             }
         }
     }
-    if note.release_timing as f32 / 1000. + 0.09 < music_timer.timer.elapsed_secs() {
-        score.miss += 1;
-        println!("end miss or short miss: {}", music_timer.timer.elapsed_secs());
+
+    //아예 안누르면 Short이든, Long이든 Miss. Short의 경우 release_timing과 timing이 동일
+    if !note.pushed && !note.missed && judge_windows.press.passed(offset) {
+        score.count(JudgeAccuracy::Miss);
+        debug!("start miss or short miss: {}", judge_time);
+        if note.note_type == NoteType::Short {
+            commands.entity(entity).despawn();
+        } else {
+            note.missed = true;
+        }
+        return (true, JudgeAccuracy::Miss, None);
+    }
+    if judge_windows.release.passed(release_offset) && note.note_type == NoteType::Long {
         commands.entity(entity).despawn();
-        return (true, JudgeAccuracy::Miss);
+        //머리를 놓쳤거나 끝까지 누르고 있어서 release window 안에 떼지 않으면 끝도 Miss
+        score.count(JudgeAccuracy::Miss);
+        debug!("end miss: {}", judge_time);
+        return (true, JudgeAccuracy::Miss, None);
     }
-    (false, JudgeAccuracy::Miss)
+    (false, JudgeAccuracy::Miss, None)
 }
 
-//'Z'
+//First lane
 pub fn despawn_note_0(
     mut commands: Commands,
-    mut query_note: Query<(Entity, &mut Note, &mut Transform)>,
-    key_input: Res<Input<KeyCode>>,
+    mut query_note: Query<(Entity, &mut Note)>,
+    lane_input: Res<LaneInput>,
+    settings: Res<Settings>,
     time: Res<Time>,
     timer: Query<(Entity, &MusicTimer, Without<Hold>)>,
     mut score: Query<&mut Scoreboard>,
     mut event_animation: EventWriter<EventAnimation>,
     mut event_key_sound: EventWriter<KeySound1>,
-    mut event_combo: EventWriter<EventCombo>,
+    judge_windows: Res<JudgeWindows>,
+    mut hit_events: EventWriter<HitEvent>,
     mut lane_query: Query<(&mut LongNoteTimer, With<FirstLane>)>
 ) {
     let (_entity, music_timer, _hold) = timer.single();
     let mut scoreboard = score.single_mut();
-    for (entity, mut note, mut transform) in query_note.iter_mut() {
+    for (entity, mut note) in query_note.iter_mut() {
         match note.press_key {
             Press4Key::First => (),
             _ => continue
         };
         let (mut lane, _dummy) = lane_query.single_mut();
         //노트 간격이 좁을 때 한번 누르는 것만으로 간격이 좁은 두 노트가 함께 제거되지 않도록 함
-        let (nest, accuracy) = despawn_note(&mut commands, key_input.clone(), KeyCode::Z, &mut note, &time, music_timer, &mut scoreboard, entity, &mut transform, &mut lane);
+        let (nest, accuracy, offset) = despawn_note(&mut commands, &lane_input, &mut note, &time, music_timer, &mut scoreboard, entity, &mut lane, &settings, &judge_windows, &mut hit_events);
         if accuracy == JudgeAccuracy::None { return; }
         if nest == true { 
-            event_animation.send(EventAnimation {judge: accuracy});
-            event_combo.send( EventCombo{ judge: accuracy });
+            event_animation.send(EventAnimation {judge: accuracy, offset});
             if accuracy != JudgeAccuracy::Miss {
                 event_key_sound.send(KeySound1);
             }
@@ -638,33 +788,34 @@ pub fn despawn_note_0(
     }
 }
 
-//'X'
+//Second lane
 pub fn despawn_note_1(
     mut commands: Commands,
-    mut query_note: Query<(Entity, &mut Note, &mut Transform)>,
-    key_input: Res<Input<KeyCode>>,
+    mut query_note: Query<(Entity, &mut Note)>,
+    lane_input: Res<LaneInput>,
+    settings: Res<Settings>,
     time: Res<Time>,
     timer: Query<(Entity, &MusicTimer, Without<Hold>)>,
     mut score: Query<&mut Scoreboard>,
     mut event_animation: EventWriter<EventAnimation>,
     mut event_key_sound: EventWriter<KeySound2>,
-    mut event_combo: EventWriter<EventCombo>,
+    judge_windows: Res<JudgeWindows>,
+    mut hit_events: EventWriter<HitEvent>,
     mut lane_query: Query<(&mut LongNoteTimer, With<SecondLane>)>,
 ) {
     let (_entity, music_timer, _hold) = timer.single();
     let mut scoreboard = score.single_mut();
-    for (entity, mut note, mut transform) in query_note.iter_mut() {
+    for (entity, mut note) in query_note.iter_mut() {
         match note.press_key {
             Press4Key::Second => (),
             _ => continue
         };
         //노트 간격이 좁을 때 한번 누르는 것만으로 간격이 좁은 두 노트가 함께 제거되지 않도록 함
         let (mut lane, _dummy) = lane_query.single_mut();
-        let (nest, accuracy) = despawn_note(&mut commands, key_input.clone(), KeyCode::X, &mut note, &time ,music_timer, &mut scoreboard, entity, &mut transform, &mut lane);
+        let (nest, accuracy, offset) = despawn_note(&mut commands, &lane_input, &mut note, &time ,music_timer, &mut scoreboard, entity, &mut lane, &settings, &judge_windows, &mut hit_events);
         if accuracy == JudgeAccuracy::None { return; }
         if nest == true { 
-            event_animation.send(EventAnimation {judge: accuracy});
-            event_combo.send( EventCombo{ judge: accuracy });
+            event_animation.send(EventAnimation {judge: accuracy, offset});
             if accuracy != JudgeAccuracy::Miss {
                 event_key_sound.send(KeySound2);
             }
@@ -673,33 +824,34 @@ pub fn despawn_note_1(
     }
 }
 
-//'.'
+//Third lane
 pub fn despawn_note_2(
     mut commands: Commands,
-    mut query_note: Query<(Entity, &mut Note, &mut Transform)>,
-    key_input: Res<Input<KeyCode>>,
+    mut query_note: Query<(Entity, &mut Note)>,
+    lane_input: Res<LaneInput>,
+    settings: Res<Settings>,
     time: Res<Time>,
     timer: Query<(Entity, &MusicTimer, Without<Hold>)>,
     mut score: Query<&mut Scoreboard>,
     mut event_animation: EventWriter<EventAnimation>,
     mut event_key_sound: EventWriter<KeySound3>,
-    mut event_combo: EventWriter<EventCombo>,
+    judge_windows: Res<JudgeWindows>,
+    mut hit_events: EventWriter<HitEvent>,
     mut lane_query: Query<(&mut LongNoteTimer, With<ThirdLane>)>,
 ) {
     let (_entity, music_timer, _hold) = timer.single();
     let mut scoreboard = score.single_mut();
-    for (entity, mut note, mut transform) in query_note.iter_mut() {
+    for (entity, mut note) in query_note.iter_mut() {
         match note.press_key {
             Press4Key::Third => (),
             _ => continue
         };
         //노트 간격이 좁을 때 한번 누르는 것만으로 간격이 좁은 두 노트가 함께 제거되지 않도록 함
         let (mut lane, _dummy) = lane_query.single_mut();
-        let (nest, accuracy) = despawn_note(&mut commands, key_input.clone(), KeyCode::Period, &mut note, &time, music_timer, &mut scoreboard, entity, &mut transform, &mut lane);
+        let (nest, accuracy, offset) = despawn_note(&mut commands, &lane_input, &mut note, &time, music_timer, &mut scoreboard, entity, &mut lane, &settings, &judge_windows, &mut hit_events);
         if accuracy == JudgeAccuracy::None { return; }
         if nest == true {
-            event_animation.send(EventAnimation {judge: accuracy});
-            event_combo.send( EventCombo{ judge: accuracy });
+            event_animation.send(EventAnimation {judge: accuracy, offset});
             if accuracy != JudgeAccuracy::Miss {
                 event_key_sound.send(KeySound3);
             }
@@ -708,33 +860,34 @@ pub fn despawn_note_2(
     }
 }
 
-//'/'
+//Fourth lane
 pub fn despawn_note_3(
     mut commands: Commands,
-    mut query_note: Query<(Entity, &mut Note, &mut Transform)>,
-    key_input: Res<Input<KeyCode>>,
+    mut query_note: Query<(Entity, &mut Note)>,
+    lane_input: Res<LaneInput>,
+    settings: Res<Settings>,
     time: Res<Time>,
     timer: Query<(Entity, &MusicTimer, Without<Hold>)>,
     mut score: Query<&mut Scoreboard>,
     mut event_animation: EventWriter<EventAnimation>,
     mut event_key_sound: EventWriter<KeySound4>,
-    mut event_combo: EventWriter<EventCombo>,
+    judge_windows: Res<JudgeWindows>,
+    mut hit_events: EventWriter<HitEvent>,
     mut lane_query: Query<(&mut LongNoteTimer, With<FourthLane>)>,
 ) {
     let (_entity, music_timer, _hold) = timer.single();
     let mut scoreboard = score.single_mut();
-    for (entity, mut note, mut transform) in query_note.iter_mut() {
+    for (entity, mut note) in query_note.iter_mut() {
         match note.press_key {
             Press4Key::Fourth => (),
             _ => continue
         };
         //노트 간격이 좁을 때 한번 누르는 것만으로 간격이 좁은 두 노트가 함께 제거되지 않도록 함
         let (mut lane, _dummy) = lane_query.single_mut();
-        let (nest, accuracy) = despawn_note(&mut commands, key_input.clone(), KeyCode::Slash, &mut note, &time, music_timer, &mut scoreboard, entity, &mut transform, &mut lane);
+        let (nest, accuracy, offset) = despawn_note(&mut commands, &lane_input, &mut note, &time, music_timer, &mut scoreboard, entity, &mut lane, &settings, &judge_windows, &mut hit_events);
         if accuracy == JudgeAccuracy::None { return; }
         if nest == true {
-            event_animation.send(EventAnimation {judge: accuracy});
-            event_combo.send( EventCombo{ judge: accuracy });
+            event_animation.send(EventAnimation {judge: accuracy, offset});
             if accuracy != JudgeAccuracy::Miss {
                 event_key_sound.send(KeySound4);
             }
@@ -745,7 +898,7 @@ pub fn despawn_note_3(
 
 pub fn spawn_keyboard_backlight(
     mut commands: Commands,
-    key_input: Res<Input<KeyCode>>,
+    lane_input: Res<LaneInput>,
     materials: Res<NoteResource>, 
     timer: Query<(Entity, &MusicTimer, Without<Hold>)>
 ) {
@@ -754,53 +907,31 @@ pub fn spawn_keyboard_backlight(
         is_paused = music_timer.timer.paused();
     }
     if !is_paused {
-        if key_input.just_pressed(KeyCode::Z) {
-            commands.spawn_bundle(SpriteBundle {
-                texture: materials.backlight.clone(),
-                transform: Transform::from_translation(Vec3::new(-151.5, 75., 1.)),
-                ..Default::default()
-            }).insert(Press4Key::First).insert(BackLight);
-        }
-        if key_input.just_pressed(KeyCode::X) {
-            commands.spawn_bundle(SpriteBundle {
-                texture: materials.backlight.clone(),
-                transform: Transform::from_translation(Vec3::new(-50.5, 75., 1.)),
-                ..Default::default()
-            }).insert(Press4Key::Second).insert(BackLight);
-        }
-        if key_input.just_pressed(KeyCode::Period) {
-            commands.spawn_bundle(SpriteBundle {
-                texture: materials.backlight.clone(),
-                transform: Transform::from_translation(Vec3::new(50.5, 75., 1.)),
-                ..Default::default()
-            }).insert(Press4Key::Third).insert(BackLight);
-        }
-        if key_input.just_pressed(KeyCode::Slash) {
-            commands.spawn_bundle(SpriteBundle {
-                texture: materials.backlight.clone(),
-                transform: Transform::from_translation(Vec3::new(151.5, 75., 1.)),
-                ..Default::default()
-            }).insert(Press4Key::Fourth).insert(BackLight);
+        let lanes = [
+            (Press4Key::First, -151.5),
+            (Press4Key::Second, -50.5),
+            (Press4Key::Third, 50.5),
+            (Press4Key::Fourth, 151.5),
+        ];
+        for (press_key, x) in lanes {
+            if lane_input.just_pressed(&press_key) {
+                commands.spawn_bundle(SpriteBundle {
+                    texture: materials.backlight.clone(),
+                    transform: Transform::from_translation(Vec3::new(x, 75., 1.)),
+                    ..Default::default()
+                }).insert(InGameEntity).insert(press_key).insert(BackLight);
+            }
         }
     }
 }
 
 pub fn despawn_keyboard_backlight(
     mut commands: Commands,
-    key_input: Res<Input<KeyCode>>,
+    lane_input: Res<LaneInput>,
     query : Query<(Entity, &BackLight, &Press4Key)>,
 ) {
     for (entity, _backlight, key_type) in query.iter() {
-        if key_input.just_released(KeyCode::Z) && (*key_type == Press4Key::First){
-            commands.entity(entity).despawn();
-        }
-        if key_input.just_released(KeyCode::X) && (*key_type == Press4Key::Second){
-            commands.entity(entity).despawn();
-        }
-        if key_input.just_released(KeyCode::Period) && (*key_type == Press4Key::Third){
-            commands.entity(entity).despawn();
-        }
-        if key_input.just_released(KeyCode::Slash) && (*key_type == Press4Key::Fourth){
+        if lane_input.just_released(key_type) {
             commands.entity(entity).despawn();
         }
     }
@@ -816,6 +947,7 @@ pub fn _print_keyboard_event_system(mut keyboard_input_events: EventReader<bevy:
 pub enum JudgeAccuracy {
     Perfect = 100,
     Great = 90,
+    Good = 60,
     Bad = 10,
     Miss = 0,
     None,
@@ -832,60 +964,54 @@ pub fn spawn_judgement(
     mut commands: Commands,
     mut events: EventReader<EventAnimation>,
     materials: Res<JudgeResource>,
+    font_resource: Res<FontResource>,
+    settings: Res<Settings>,
 ) {
     let mut judge_transform = Transform::from_translation(Vec3::new(0., -50., 4.));
-    for accuracy in events.iter() {
-        match accuracy {
-            EventAnimation {judge : JudgeAccuracy::Perfect} => {
-                let timer = JudgeTimer(Timer::from_seconds(1.5, false));
-                let scale = Scale(1.0);
-                judge_transform.scale = Vec3::splat(scale.0);
-                commands.spawn_bundle( SpriteBundle {
-                    texture: materials.perfect.clone(),
-                    transform: judge_transform,
-                    ..Default::default()
-                }).insert(timer).insert(scale);
-                return;
-            },
-    
-            EventAnimation {judge : JudgeAccuracy::Great} => {
-                let timer = JudgeTimer(Timer::from_seconds(1.5, false));
-                let scale = Scale(1.0);
-                judge_transform.scale = Vec3::splat(scale.0);
-                commands.spawn_bundle( SpriteBundle {
-                    texture: materials.great.clone(),
-                    transform: judge_transform,
-                    ..Default::default()
-                }).insert(timer).insert(scale);
-                return;
-            },
-
-            EventAnimation {judge : JudgeAccuracy::Bad} => {
-                let timer = JudgeTimer(Timer::from_seconds(1.5, false));
-                let scale = Scale(1.0);
-                judge_transform.scale = Vec3::splat(scale.0);
-                commands.spawn_bundle( SpriteBundle {
-                    texture: materials.bad.clone(),
-                    transform: judge_transform,
-                    ..Default::default()
-                }).insert(timer).insert(scale);
-                return;
-            },
-    
-            EventAnimation {judge : JudgeAccuracy::Miss} => {
-                let timer = JudgeTimer(Timer::from_seconds(1.5, false));
-                let scale = Scale(1.0);
-                judge_transform.scale = Vec3::splat(scale.0);
-                commands.spawn_bundle( SpriteBundle {
-                    texture: materials.miss.clone(),
-                    transform: judge_transform,
-                    ..Default::default()
-                }).insert(timer).insert(scale);
-                return;
-            },
-
+    for event in events.iter() {
+        let texture = match event.judge {
+            JudgeAccuracy::Perfect => materials.perfect.clone(),
+            JudgeAccuracy::Great => materials.great.clone(),
+            JudgeAccuracy::Good => materials.good.clone(),
+            JudgeAccuracy::Bad => materials.bad.clone(),
+            JudgeAccuracy::Miss => materials.miss.clone(),
             _ => return
+        };
+        let timer = JudgeTimer(Timer::from_seconds(1.5, false));
+        let scale = Scale(1.0);
+        judge_transform.scale = Vec3::splat(scale.0);
+        let mut judgement = commands.spawn_bundle( SpriteBundle {
+            texture,
+            transform: judge_transform,
+            ..Default::default()
+        });
+        judgement.insert(timer).insert(scale).insert(InGameEntity);
+
+        //판정 sprite 오른쪽에 EARLY/LATE와 offset을 표시. sprite와 함께 작아지고 사라짐
+        if let Some(offset) = event.offset {
+            if settings.early_late.shows(event.judge) {
+                let (label, color) = if offset < 0. {
+                    ("EARLY", EARLY_COLOR)
+                } else {
+                    ("LATE", LATE_COLOR)
+                };
+                judgement.with_children(|parent| {
+                    parent.spawn_bundle(Text2dBundle {
+                        transform: Transform::from_translation(Vec3::new(EARLY_LATE_X, 0., 0.1)),
+                        text: Text::from_section(
+                            format!("{}\n{:+.0}ms", label, offset),
+                            TextStyle {
+                                font: font_resource.font.clone(),
+                                font_size: 28.,
+                                color,
+                            }
+                        ).with_alignment(TextAlignment::CENTER),
+                        ..Default::default()
+                    });
+                });
+            }
         }
+        return;
     }
 }
 
@@ -906,7 +1032,7 @@ pub fn update_judgement(
             scale.0 -= 0.02;
         }
         if timer.0.finished() || query_number >= 2 {
-            commands.entity(entity).despawn();
+            commands.entity(entity).despawn_recursive();
             query_number -= 1;
         }
     }
@@ -948,7 +1074,8 @@ pub fn setup_background_text(
         },
         ..default()
     })
-    .insert(TimerText);
+    .insert(TimerText)
+    .insert(InGameEntity);
 
     commands.spawn_bundle(TextBundle {
         node: Node {size: Vec2::new(500., 100.)},
@@ -996,6 +1123,23 @@ pub fn setup_background_text(
                     }
                 },
 
+                TextSection {
+                    value: "\nGood : ".to_string(),
+                    style: TextStyle {
+                        font: font_resource.font.clone(),
+                        font_size: 20.0,
+                        color: Color::GOLD,
+                    }
+                },
+                TextSection {
+                    value: "0".to_string(),
+                    style: TextStyle {
+                        font: font_resource.font.clone(),
+                        font_size: 20.0,
+                        color: Color::GOLD,
+                    }
+                },
+
                 TextSection {
                     value: "\nBad : ".to_string(),
                     style: TextStyle {
@@ -1034,7 +1178,7 @@ pub fn setup_background_text(
         },
         //transform: Transform::from_translation(Vec3::new(-350., 450., 10.)),
         ..default()
-    }).insert(Scoreboard {perfect:0, great:0, miss:0, bad:0,});
+    }).insert(InGameEntity).insert(Scoreboard::default());
 }
 
 pub fn update_background_text(
@@ -1053,8 +1197,9 @@ pub fn update_scoreboard(
         let (mut score_text, scoreboard) = score_query.single_mut();
         score_text.sections[1].value = scoreboard.perfect.to_string();
         score_text.sections[3].value = scoreboard.great.to_string();
-        score_text.sections[5].value = scoreboard.bad.to_string();
-        score_text.sections[7].value = scoreboard.miss.to_string();
+        score_text.sections[5].value = scoreboard.good.to_string();
+        score_text.sections[7].value = scoreboard.bad.to_string();
+        score_text.sections[9].value = scoreboard.miss.to_string();
 }
 
 pub fn setup_accuracy(
@@ -1072,8 +1217,8 @@ pub fn setup_accuracy(
             }
         ).with_alignment(TextAlignment::CENTER),
         ..Default::default()
-        }).insert(AccuracyText);
-        commands.spawn().insert(TotalAccuracy(0.));
+        }).insert(InGameEntity).insert(AccuracyText);
+        commands.spawn().insert(TotalAccuracy(0.)).insert(InGameEntity);
 }
 
 pub fn update_accuracy(
@@ -1083,12 +1228,10 @@ pub fn update_accuracy(
 ) {
     let mut accuracy = accuracy_query.single_mut();
     let scoreboard = scoreboard_query.single();
-    let total_score = 100 * (scoreboard.perfect + scoreboard.great + scoreboard.miss);
-    let total;
-    if total_score != 0 {
-        total = (scoreboard.perfect * 100 + scoreboard.great * 90) as f32 / total_score as f32;
-    } else {return;}
-    accuracy.0 = total * 100.;
+    if scoreboard.judged() == 0 {
+        return;
+    }
+    accuracy.0 = score::accuracy(scoreboard);
     let total_text = format!("{:0.02}%", accuracy.0);
     
     let (mut text, _dummy) = text_query.single_mut();
@@ -1110,40 +1253,59 @@ pub fn setup_combo(
             }
         ).with_alignment(TextAlignment::CENTER),
         ..Default::default()
-        }).insert(ComboText);
-    commands.spawn().insert(Combo(0));
+        }).insert(InGameEntity).insert(ComboText);
 }
 
-//차후에 ComboResource로 그림수정
-/*
-pub fn spawn_combo_effect(
-    mut commands: Commands,
-    mut event_combo: EventReader<EventCombo>,
-    query: Query<(&Combo)>
-) {
-
-}
-*/
-
+//combo는 Scoreboard에서 판정 단위로 셈
 pub fn update_combo_effect(
-    mut combo_query: Query<&mut Combo>,
+    scoreboard_query: Query<&Scoreboard>,
     mut text_query: Query<(&mut Text, &ComboText)>,
-    mut event_combo: EventReader<EventCombo>
 ) {
-    let mut combo = combo_query.single_mut();
-    for event in event_combo.iter() {
-        match event.judge {
-            JudgeAccuracy::Miss => {combo.0 = 0},
-            _ => {combo.0 += 1},
-        }
-    }
-    let combo_text = combo.0;
-
+    let scoreboard = scoreboard_query.single();
     let (mut text, _dummy) = text_query.single_mut();
-    text.sections[0].value = combo_text.to_string();
+    text.sections[0].value = scoreboard.combo.to_string();
 }
 
 
+pub fn setup_scroll_speed_text(
+    mut commands: Commands,
+    font_resource: Res<FontResource>,
+    settings: Res<Settings>,
+) {
+    commands.spawn_bundle(Text2dBundle {
+        transform: Transform::from_translation(Vec3::new(300., -420., 4.)),
+        text: Text::from_section(
+            format!("Scroll x{:.1}", settings.scroll_speed),
+            TextStyle {
+                font: font_resource.font.clone(),
+                font_size: 20.,
+                color: Color::rgba(0.98, 0.92, 0.92, 0.5)
+            }
+        ).with_alignment(TextAlignment::CENTER),
+        ..Default::default()
+        }).insert(InGameEntity).insert(ScrollSpeedText);
+}
+
+//플레이 중에 scroll speed를 바꿈. note 위치는 move_note에서 새 속도로 다시 계산됨
+//파일 저장은 플레이 중에 frame이 끊기지 않도록 InGame을 나갈 때 save_scroll_speed에서 함
+pub fn change_scroll_speed(
+    key_input: Res<Input<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut text_query: Query<&mut Text, With<ScrollSpeedText>>,
+) {
+    let direction = if key_input.just_pressed(SCROLL_SPEED_UP_KEY) {
+        1
+    } else if key_input.just_pressed(SCROLL_SPEED_DOWN_KEY) {
+        -1
+    } else {
+        return;
+    };
+    settings.step_scroll_speed(direction);
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format!("Scroll x{:.1}", settings.scroll_speed);
+    }
+}
 
 
 //for debug
@@ -1170,7 +1332,7 @@ pub fn pause_game(
                 texture: materials.pause.clone(),
                 transform: Transform::from_translation(Vec3::new(275., 0., 3.)),
                 ..Default::default()
-            }).insert(PausedText);
+            }).insert(InGameEntity).insert(PausedText);
         } else if key_input.just_pressed(KeyCode::Escape) && music_timer.timer.paused() {
             music_timer.timer.unpause();
             for (entity, _paused_text) in text.iter() {
@@ -1192,42 +1354,116 @@ pub fn print_keyboard_event_system(
     }
 }
 
-pub fn open_chart(mut commands: Commands) {
-    let chart_file = File::open("assets/music/PUPA/PUPA.txt").expect("file not found");
+pub fn open_chart(
+    mut commands: Commands,
+    selected_song: Res<SelectedSong>,
+    asset_server: Res<AssetServer>,
+    charts: Res<Assets<ChartAsset>>,
+    settings: Res<Settings>,
+    mut judgement_total: ResMut<JudgementTotal>,
+) {
+    //loading 중이거나 오류가 있는 chart는 select menu에서 InGame으로 보내지 않으므로 여기까지 오지 않음
+    let chart = match selected_song.get(&asset_server, &charts) {
+        Some(Ok(chart)) => chart,
+        _ => return,
+    };
+
+    spawn_chart(&mut commands, chart, 0, settings.note_speed);
+    judgement_total.0 = score::judgement_total(&chart.notes);
+
+    //Music은 최대 MAX_MUSIC_LENGTH / 1000 만큼의 길이를 가짐
+    let music_timer = MusicTimer {timer: Timer::from_seconds(MAX_MUSIC_LENGTH / 1000., false)};
+    commands.spawn().insert(music_timer).insert(InGameEntity);
+
+    //게임시작하고 HOLD_TIME / 1000만큼 대기
+    let hold_timer = MusicTimer { timer: Timer::from_seconds(HOLD_TIME / 1000., false)}; 
+    commands.spawn().insert(hold_timer).insert(Hold).insert(InGameEntity);
+
+}
+
+//모든 Chart가 비고 마지막 Note까지 판정되면 RESULT_DELAY 후 Result로 이동
+pub fn check_game_end(
+    time: Res<Time>,
+    mut end_timer: Local<Option<Timer>>,
+    chart_query: Query<&Chart>,
+    note_query: Query<&Note>,
+    score_query: Query<&Scoreboard>,
+    accuracy_query: Query<&TotalAccuracy>,
+    hit_log: Res<HitLog>,
+    judgement_total: Res<JudgementTotal>,
+    settings: Res<Settings>,
+    mut play_result: ResMut<PlayResult>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !chart_query.is_empty() || !note_query.is_empty() {
+        *end_timer = None;
+        return;
+    }
+
+    let timer = end_timer.get_or_insert_with(|| Timer::from_seconds(RESULT_DELAY, false));
+    timer.tick(time.delta());
+    if !timer.finished() {
+        return;
+    }
+    *end_timer = None;
+
+    let scoreboard = score_query.single();
+    *play_result = PlayResult {
+        perfect: scoreboard.perfect,
+        great: scoreboard.great,
+        good: scoreboard.good,
+        bad: scoreboard.bad,
+        miss: scoreboard.miss,
+        score: score::score(scoreboard, judgement_total.0, settings.combo_bonus),
+        accuracy: accuracy_query.single().0,
+        max_combo: scoreboard.max_combo as u32,
+        hit_errors: hit_log.press_offsets(),
+    };
+    state.set(GameState::Result).unwrap();
+}
+
+//InGame에서 spawn한 entity만 정리
+pub fn despawn_game(
+    mut commands: Commands,
+    query: Query<Entity, With<InGameEntity>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
 
+//start_timing(ms) 이후의 note만 lane별 Chart entity로 spawn. note 속도는 설정값을 사용
+fn spawn_chart(
+    commands: &mut Commands,
+    chart: &ChartFile,
+    start_timing: usize,
+    note_speed: f32,
+) {
     //Note를 Spawn하거나 Despawn할 때 한번에 4개를 동시에 처리할 수 있도록 저장하는 Stack을 나눔
     let mut chart_vec_0: VecDeque<Note> = VecDeque::new();
     let mut chart_vec_1: VecDeque<Note> = VecDeque::new();
     let mut chart_vec_2: VecDeque<Note> = VecDeque::new();
     let mut chart_vec_3: VecDeque<Note> = VecDeque::new();
 
-    let mut buffer = BufReader::new(chart_file);
-    let mut line: String = String::new();
-
-    loop {
-        let read_bytes = buffer.read_line(&mut line).unwrap();
-        //println!("Buffer: {}", line.trim());
-        //println!("Read Bytes: {}", read_bytes);
-        if read_bytes == 0 {
-            break;
-        }
-        
-        let parsed_note = parse_file_string(&line).unwrap();
-        match parsed_note.press_key {
+    for note in chart.notes.iter().filter(|note| note.timing >= start_timing) {
+        let note = Note {
+            speed: note_speed,
+            ..note.clone()
+        };
+        match note.press_key {
             Press4Key::First => {
-                chart_vec_0.push_back(parsed_note);
+                chart_vec_0.push_back(note);
             },
             Press4Key::Second => {
-                chart_vec_1.push_back(parsed_note);
+                chart_vec_1.push_back(note);
             },
             Press4Key::Third => {
-                chart_vec_2.push_back(parsed_note);
+                chart_vec_2.push_back(note);
             },
             Press4Key::Fourth => { 
-                chart_vec_3.push_back(parsed_note);
+                chart_vec_3.push_back(note);
             }
         }
-        line.clear();
     }
 
     //먼저 눌러야하는 순으로 정렬하여 나중에 spawn_note system에서 사용이 더 용이하도록 함
@@ -1249,91 +1485,139 @@ pub fn open_chart(mut commands: Commands) {
         notes: chart_vec_3,
     };
 
+    //마지막 note까지 timing point에 맞춰 마디선을 미리 spawn. 위치는 move_bar_line에서 계산
+    let end_timing = chart.notes.iter().map(|note| note.release_timing).max().unwrap_or(0);
+    for timing in chart.timing.bar_lines(end_timing as f32) {
+        if timing < start_timing as f32 {
+            continue;
+        }
+        commands.spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: BAR_LINE_COLOR,
+                custom_size: Some(Vec2::new(BAR_LINE_WIDTH, 2.)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(Vec3::new(0., 1000., 2.)),
+            ..Default::default()
+        }).insert(InGameEntity).insert(BarLine { timing });
+    }
+
+    commands.insert_resource(chart.scroll.clone());
+
     //Resource가 아닌 Entity로써 Chart를 관리하여 수정, 삭제를 용이하게 함
-    commands.spawn().insert(chart_0).insert(FirstLane);
-    commands.spawn().insert(chart_1).insert(SecondLane);
-    commands.spawn().insert(chart_2).insert(ThirdLane);
-    commands.spawn().insert(chart_3).insert(FourthLane);
+    commands.spawn().insert(chart_0).insert(FirstLane).insert(InGameEntity);
+    commands.spawn().insert(chart_1).insert(SecondLane).insert(InGameEntity);
+    commands.spawn().insert(chart_2).insert(ThirdLane).insert(InGameEntity);
+    commands.spawn().insert(chart_3).insert(FourthLane).insert(InGameEntity);
+}
 
-    //Music은 최대 MAX_MUSIC_LENGTH / 1000 만큼의 길이를 가짐
-    let music_timer = MusicTimer {timer: Timer::from_seconds(MAX_MUSIC_LENGTH / 1000., false)};
-    commands.spawn().insert(music_timer);
+//chart file이 수정되면 현재 MusicTimer 위치를 유지한 채 아직 지나가지 않은 note들로 Chart를 다시 만듦
+pub fn reload_chart(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<ChartAsset>>,
+    selected_song: Res<SelectedSong>,
+    charts: Res<Assets<ChartAsset>>,
+    chart_query: Query<Entity, With<Chart>>,
+    note_query: Query<Entity, With<Note>>,
+    bar_line_query: Query<Entity, With<BarLine>>,
+    timer: Query<&MusicTimer, Without<Hold>>,
+    settings: Res<Settings>,
+    score_query: Query<&Scoreboard>,
+    mut judgement_total: ResMut<JudgementTotal>,
+) {
+    for event in events.iter() {
+        let handle = match event {
+            AssetEvent::Modified { handle } => handle,
+            _ => continue,
+        };
+        if *handle != selected_song.chart {
+            continue;
+        }
 
-    //게임시작하고 HOLD_TIME / 1000만큼 대기
-    let hold_timer = MusicTimer { timer: Timer::from_seconds(HOLD_TIME / 1000., false)}; 
-    commands.spawn().insert(hold_timer).insert(Hold);
+        let chart = match charts.get(handle).map(|asset| &asset.chart) {
+            Some(Ok(chart)) => chart,
+            Some(Err(errors)) => {
+                //수정 중인 chart에 오류가 있으면 기존 chart로 계속 플레이
+                for error in errors.iter() {
+                    warn!("{}: {}", selected_song.chart_path, error);
+                }
+                continue;
+            },
+            None => continue,
+        };
 
+        for entity in chart_query.iter().chain(note_query.iter()).chain(bar_line_query.iter()) {
+            commands.entity(entity).despawn();
+        }
+
+        let music_timer = timer.single();
+        //note timing은 판정 시각 기준이므로 audio offset을 적용한 시각부터 다시 spawn
+        let judge_time = settings.judge_time(music_timer.timer.elapsed_secs());
+        let start_timing = (judge_time.max(0.) * 1000.) as usize;
+        spawn_chart(&mut commands, chart, start_timing, settings.note_speed);
+        //이미 판정된 수에 다시 spawn한 note의 판정 수를 더함
+        let remaining = chart.notes.iter().filter(|note| note.timing >= start_timing);
+        judgement_total.0 = score_query.single().judged() + score::judgement_total(remaining);
+        info!("Chart reloaded: {}", selected_song.chart_path);
+    }
 }
 
-fn parse_file_string(string: &String) -> Result<Note, &'static str> {
-    let mut start: usize = 0;
-    let mut end: usize = 0;
-    let mut commas: usize = 0;
+//lane,Short|Long,timing,release, 형식의 한 줄을 Note로 변환
+//실패시 오류가 발생한 column(0부터 시작)과 이유를 반환
+pub fn parse_file_string(string: &str, timing: &TimingMap) -> Result<Note, (usize, String)> {
     let mut note = Note {
         note_type: NoteType::Short,
         press_key: Press4Key::First,
         timing: 0,
         release_timing: 0,
-        speed: 17.4, // 6.4? = 17.4?
+        speed: DEFAULT_NOTE_SPEED,
         pushed: false,
         missed: false,
         judge: JudgeAccuracy::None,
     };
-    //println!("parsed string: {}", string.trim());
-    for c in string.chars() {
-        if c == ',' {
-            match commas {
-                0 => { 
-                    let parsed: usize = (&string[start..end]).parse().unwrap();
-                    start = end + 1;
-                    //println!("commas 0: {}", parsed);
-                    match parsed {
-                        0 => note.press_key = Press4Key::First,
-                        1 => note.press_key = Press4Key::Second,
-                        2 => note.press_key = Press4Key::Third,
-                        3 => note.press_key = Press4Key::Fourth,
-                        _ => panic!("parsing press_key error")
-                    }
-                }
-                
-                1 => {
-                    let parsed: &str = &string[start..end];
-                    start = end + 1;
-                    //println!("commas 1: {}", parsed);
-                    match parsed {
-                        "Short" => note.note_type = NoteType::Short,
-                        "Long" => note.note_type = NoteType::Long,
-                        _ => panic!("parsing type error")
-                    }
-                }
 
-                2 => {
-                    let parsed: usize = (&string[start..end]).parse().unwrap();
-                    start = end + 1;
-                    //println!("commas 2: {}\n", parsed);
-                    note.timing = parsed;
-                    note.release_timing = parsed;
-                }
-                3 => {
-                    if note.note_type == NoteType::Short {
-                      break;
-                    }
-                    let parsed: usize = (&string[start..end]).parse().unwrap();
-                    note.release_timing = parsed;
-                }
-                4 => {
-                    break;
-                }
-                _ => panic!("parsing comma error : {}", commas),
-            }
-            commas = commas + 1;
-        } 
-        end = end + 1;
+    //각 field와 field가 시작하는 column을 함께 저장
+    let mut fields: Vec<(usize, &str)> = Vec::new();
+    let mut start: usize = 0;
+    for field in string.split(',') {
+        fields.push((start + field.len() - field.trim_start().len(), field.trim()));
+        start += field.len() + 1;
     }
 
-    if commas == 3 || commas == 4 {
-        Ok(note)
-    } else {
-        Err("Not parsed")
+    if fields.len() < 3 {
+        return Err((string.len(), "expected 'lane,type,timing,'".to_string()));
+    }
+
+    let (column, field) = fields[0];
+    note.press_key = match field {
+        "0" => Press4Key::First,
+        "1" => Press4Key::Second,
+        "2" => Press4Key::Third,
+        "3" => Press4Key::Fourth,
+        _ => return Err((column, format!("unknown lane '{}', expected 0 to 3", field))),
+    };
+
+    let (column, field) = fields[1];
+    note.note_type = match field {
+        "Short" => NoteType::Short,
+        "Long" => NoteType::Long,
+        _ => return Err((column, format!("unknown note type '{}', expected Short or Long", field))),
+    };
+
+    let (column, field) = fields[2];
+    note.timing = timing.parse_time(field).ok_or_else(|| (column, format!("invalid timing '{}'", field)))?;
+    note.release_timing = note.timing;
+
+    if note.note_type == NoteType::Long {
+        let (column, field) = match fields.get(3) {
+            Some(&(column, field)) if !field.is_empty() => (column, field),
+            _ => return Err((string.len(), "long note requires a release timing".to_string())),
+        };
+        note.release_timing = timing.parse_time(field).ok_or_else(|| (column, format!("invalid release timing '{}'", field)))?;
+        if note.release_timing < note.timing {
+            return Err((column, format!("release timing {} is earlier than timing {}", note.release_timing, note.timing)));
+        }
     }
-}
\ No newline at end of file
+
+    Ok(note)
+}
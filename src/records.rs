@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use bevy::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::state::{GameState, pop_on_keys};
+use crate::notes::FontResource;
+use crate::library::{SongLibrary, SelectedSong};
+use crate::chart::ChartAsset;
+use crate::result::{PlayResult, grade};
+use crate::score::format_score;
+use crate::settings::{load_config, save_config};
+
+const TEXT_COLOR: Color = Color::rgba(0.9, 0.9, 0.9, 1.);
+const RECORDS_FILE: &str = "records.ron";
+
+pub struct RecordsPlugin;
+
+impl Plugin for RecordsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Records::load())
+            .add_system_set(
+                SystemSet::on_enter(GameState::Result)
+                .with_system(save_record)
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Records)
+                .with_system(setup_records)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Records)
+                .with_system(close_records)
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Records)
+                .with_system(despawn_records)
+            );
+    }
+}
+
+//chart 하나의 최고 기록
+#[derive(Serialize, Deserialize)]
+pub struct ChartRecord {
+    pub title: String,
+    pub difficulty: String,
+    pub level: u32,
+    pub best_score: u32,
+    pub best_accuracy: f32,
+    pub max_combo: u32,
+    pub plays: u32,
+}
+
+//chart 경로별 최고 기록. 설정과 같은 config 폴더의 records.ron에 저장
+#[derive(Default, Serialize, Deserialize)]
+pub struct Records {
+    pub charts: HashMap<String, ChartRecord>,
+}
+
+impl Records {
+    //기록 파일이 없거나 읽을 수 없으면 빈 기록으로 시작
+    pub fn load() -> Self {
+        match load_config(RECORDS_FILE) {
+            Ok(records) => records.unwrap_or_default(),
+            Err(error) => {
+                warn!("{}", error);
+                Records::default()
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct RecordsScreen;
+
+//Result에 들어갈 때 이번 결과로 기록을 갱신. 기록은 chart 경로로 구분
+pub fn save_record(
+    mut records: ResMut<Records>,
+    play_result: Res<PlayResult>,
+    library: Res<SongLibrary>,
+    selected_song: Res<SelectedSong>,
+    asset_server: Res<AssetServer>,
+    charts: Res<Assets<ChartAsset>>,
+) {
+    //곡 정보는 플레이한 chart의 metadata에서 가져옴
+    let song_name = library.find_chart(&selected_song.chart).map(|(song, _)| song.name.clone());
+    let (title, difficulty, level) = match selected_song.get(&asset_server, &charts) {
+        Some(Ok(chart)) if !chart.metadata.title.is_empty() => {
+            (chart.metadata.title.clone(), chart.metadata.difficulty.clone(), chart.metadata.level)
+        },
+        Some(Ok(chart)) => {
+            (song_name.unwrap_or_else(|| selected_song.chart_path.clone()), chart.metadata.difficulty.clone(), chart.metadata.level)
+        },
+        //플레이 후에 chart가 깨졌으면 library에 남아있는 정보를 사용
+        _ => match library.find_chart(&selected_song.chart) {
+            Some((song, chart_info)) => (song.name.clone(), chart_info.difficulty.clone(), chart_info.level),
+            None => (selected_song.chart_path.clone(), String::new(), 0),
+        },
+    };
+
+    let record = records.charts.entry(selected_song.chart_path.clone()).or_insert(ChartRecord {
+        title: String::new(),
+        difficulty: String::new(),
+        level: 0,
+        best_score: 0,
+        best_accuracy: 0.,
+        max_combo: 0,
+        plays: 0,
+    });
+    record.title = title;
+    record.difficulty = difficulty;
+    record.level = level;
+
+    record.best_score = record.best_score.max(play_result.score);
+    record.best_accuracy = record.best_accuracy.max(play_result.accuracy);
+    record.max_combo = record.max_combo.max(play_result.max_combo);
+    record.plays += 1;
+    save_config(RECORDS_FILE, &*records);
+}
+
+pub fn setup_records(
+    mut commands: Commands,
+    font_resource: Res<FontResource>,
+    records: Res<Records>,
+) {
+    let text_style = |font_size: f32| TextStyle {
+        font: font_resource.font.clone(),
+        font_size,
+        color: TEXT_COLOR,
+    };
+
+    let mut charts: Vec<&ChartRecord> = records.charts.values().collect();
+    charts.sort_by(|a, b| a.title.cmp(&b.title).then_with(|| a.level.cmp(&b.level)));
+
+    let mut sections = vec![TextSection {
+        value: "Records\n\n".to_string(),
+        style: text_style(40.),
+    }];
+    if charts.is_empty() {
+        sections.push(TextSection {
+            value: "No records yet\n".to_string(),
+            style: text_style(22.),
+        });
+    }
+    for record in charts {
+        sections.push(TextSection {
+            value: format!(
+                "{} [{} {}]   {}  {}  {:0.02}%   Max Combo {}   Plays {}\n",
+                record.title, record.difficulty, record.level, grade(record.best_accuracy),
+                format_score(record.best_score), record.best_accuracy, record.max_combo, record.plays
+            ),
+            style: text_style(22.),
+        });
+    }
+    sections.push(TextSection {
+        value: "\nPress Esc or Enter to go back".to_string(),
+        style: text_style(18.),
+    });
+
+    commands.spawn_bundle(TextBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect::new(Val::Px(30.), Val::Auto, Val::Px(30.), Val::Auto),
+            max_size: Size::new(Val::Px(940.), Val::Undefined),
+            ..Default::default()
+        },
+        text: Text {
+            sections,
+            ..Default::default()
+        },
+        ..Default::default()
+    }).insert(RecordsScreen);
+}
+
+pub fn close_records(
+    mut key_input: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    pop_on_keys(&mut key_input, &mut state, &[KeyCode::Escape, KeyCode::Return]);
+}
+
+pub fn despawn_records(
+    mut commands: Commands,
+    query: Query<Entity, With<RecordsScreen>>
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
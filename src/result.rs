@@ -0,0 +1,268 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use crate::state::{GameState, take_key, pop_on_keys};
+use crate::notes::FontResource;
+use crate::library::{SongLibrary, SelectedSong};
+use crate::chart::ChartAsset;
+use crate::score::format_score;
+
+//hit error histogram 범위(ms)와 막대 하나의 폭(ms)
+const HISTOGRAM_RANGE: f32 = 90.;
+const HISTOGRAM_BIN: f32 = 10.;
+const HISTOGRAM_WIDTH: f32 = 500.;
+const HISTOGRAM_HEIGHT: f32 = 120.;
+const TEXT_COLOR: Color = Color::rgba(0.9, 0.9, 0.9, 1.);
+const BUTTON_COLOR: Color = Color::rgba(0.2, 0.2, 0.2, 0.8);
+const BUTTON_HOVER_COLOR: Color = Color::rgba(0.9, 0.55, 0.2, 0.9);
+
+pub struct ResultPlugin;
+
+impl Plugin for ResultPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<PlayResult>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Result)
+                .with_system(setup_result)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Result)
+                .with_system(result_button_interaction)
+                .with_system(result_keyboard)
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Result)
+                .with_system(despawn_result)
+            );
+    }
+}
+
+//InGame이 끝날 때 채워지는 한 판의 결과
+#[derive(Default)]
+pub struct PlayResult {
+    pub perfect: usize,
+    pub great: usize,
+    pub good: usize,
+    pub bad: usize,
+    pub miss: usize,
+    pub score: u32,
+    pub accuracy: f32,
+    pub max_combo: u32,
+    //ms. 음수면 빨리 누름
+    pub hit_errors: Vec<f32>,
+}
+
+#[derive(Component)]
+pub struct ResultScreen;
+
+#[derive(Component)]
+pub enum ResultButton {
+    Retry,
+    Back,
+}
+
+pub fn grade(accuracy: f32) -> &'static str {
+    match accuracy {
+        x if x >= 95. => "S",
+        x if x >= 90. => "A",
+        x if x >= 80. => "B",
+        x if x >= 70. => "C",
+        _ => "D",
+    }
+}
+
+pub fn setup_result(
+    mut commands: Commands,
+    font_resource: Res<FontResource>,
+    play_result: Res<PlayResult>,
+    library: Res<SongLibrary>,
+    selected_song: Res<SelectedSong>,
+) {
+    let text_style = |font_size: f32| TextStyle {
+        font: font_resource.font.clone(),
+        font_size,
+        color: TEXT_COLOR,
+    };
+
+    let title = match library.find_chart(&selected_song.chart) {
+        Some((song, chart_info)) => format!("{}  [{} {}]", song.name, chart_info.difficulty, chart_info.level),
+        None => selected_song.chart_path.clone(),
+    };
+    let counts = format!(
+        "Perfect {}   Great {}   Good {}   Bad {}   Miss {}",
+        play_result.perfect, play_result.great, play_result.good, play_result.bad, play_result.miss
+    );
+    let summary = format!(
+        "Score {}   Accuracy {:0.02}%   Max Combo {}",
+        format_score(play_result.score), play_result.accuracy, play_result.max_combo
+    );
+
+    commands.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        parent.spawn_bundle(TextBundle {
+            text: Text::from_section(title, text_style(30.)),
+            ..Default::default()
+        });
+        parent.spawn_bundle(TextBundle {
+            text: Text::from_section(grade(play_result.accuracy), text_style(120.)),
+            ..Default::default()
+        });
+        parent.spawn_bundle(TextBundle {
+            text: Text::from_section(counts, text_style(24.)),
+            ..Default::default()
+        });
+        parent.spawn_bundle(TextBundle {
+            text: Text::from_section(summary, text_style(24.)),
+            ..Default::default()
+        });
+
+        spawn_histogram(parent, &play_result.hit_errors);
+        parent.spawn_bundle(TextBundle {
+            text: Text::from_section(
+                format!("Early  -{}ms          0          +{}ms  Late", HISTOGRAM_RANGE, HISTOGRAM_RANGE),
+                text_style(16.)
+            ),
+            ..Default::default()
+        });
+
+        parent.spawn_bundle(NodeBundle {
+            style: Style {
+                margin: UiRect::all(Val::Px(20.)),
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        }).with_children(|parent| {
+            spawn_result_button(parent, text_style(24.), "Retry (R)", ResultButton::Retry);
+            spawn_result_button(parent, text_style(24.), "Back (Enter)", ResultButton::Back);
+        });
+    }).insert(ResultScreen);
+}
+
+fn spawn_histogram(parent: &mut ChildBuilder, hit_errors: &[f32]) {
+    let bin_count = (2. * HISTOGRAM_RANGE / HISTOGRAM_BIN).ceil() as usize;
+    let mut bins = vec![0usize; bin_count];
+    for hit_error in hit_errors.iter() {
+        let bin = ((hit_error + HISTOGRAM_RANGE) / HISTOGRAM_BIN).floor();
+        if bin >= 0. {
+            bins[(bin as usize).min(bin_count - 1)] += 1;
+        }
+    }
+    let max_count = bins.iter().cloned().max().unwrap_or(0).max(1);
+
+    parent.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Px(HISTOGRAM_WIDTH), Val::Px(HISTOGRAM_HEIGHT)),
+            margin: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(20.), Val::Px(5.)),
+            align_items: AlignItems::FlexStart,
+            ..Default::default()
+        },
+        color: Color::rgba(0.1, 0.1, 0.1, 0.5).into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        for (index, count) in bins.iter().enumerate() {
+            //가운데(0ms)에 가까울수록 밝게
+            let center = (index as f32 + 0.5) * HISTOGRAM_BIN - HISTOGRAM_RANGE;
+            let brightness = 1. - center.abs() / HISTOGRAM_RANGE * 0.6;
+            parent.spawn_bundle(NodeBundle {
+                style: Style {
+                    size: Size::new(
+                        Val::Px(HISTOGRAM_WIDTH / bin_count as f32),
+                        Val::Percent(*count as f32 / max_count as f32 * 100.)
+                    ),
+                    ..Default::default()
+                },
+                color: Color::rgba(0.9 * brightness, 0.55 * brightness, 0.2, 0.9).into(),
+                ..Default::default()
+            });
+        }
+    });
+}
+
+fn spawn_result_button(parent: &mut ChildBuilder, style: TextStyle, text: &str, button: ResultButton) {
+    parent.spawn_bundle(ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(200.), Val::Px(50.)),
+            margin: UiRect::all(Val::Px(10.)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: BUTTON_COLOR.into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        parent.spawn_bundle(TextBundle {
+            text: Text::from_section(text, style),
+            focus_policy: FocusPolicy::Pass,
+            ..Default::default()
+        });
+    }).insert(button);
+}
+
+//select_song과 같이 chart를 다시 확인. 플레이 중에 수정되어 오류가 생겼으면 ChartError를 보여줌
+fn retry(
+    state: &mut State<GameState>,
+    selected_song: &SelectedSong,
+    asset_server: &AssetServer,
+    charts: &Assets<ChartAsset>,
+) {
+    match selected_song.get(asset_server, charts) {
+        Some(Ok(_)) => state.set(GameState::InGame).unwrap(),
+        Some(Err(_)) => state.set(GameState::ChartError).unwrap(),
+        None => (),
+    }
+}
+
+pub fn result_button_interaction(
+    mut interaction_query: Query<(&Interaction, &ResultButton, &mut UiColor), Changed<Interaction>>,
+    mut state: ResMut<State<GameState>>,
+    selected_song: Res<SelectedSong>,
+    asset_server: Res<AssetServer>,
+    charts: Res<Assets<ChartAsset>>,
+) {
+    for (interaction, button, mut color) in interaction_query.iter_mut() {
+        match interaction {
+            Interaction::Clicked => {
+                match button {
+                    ResultButton::Retry => retry(&mut state, &selected_song, &asset_server, &charts),
+                    ResultButton::Back => state.pop().unwrap(),
+                }
+                return;
+            },
+            Interaction::Hovered => color.0 = BUTTON_HOVER_COLOR,
+            Interaction::None => color.0 = BUTTON_COLOR,
+        }
+    }
+}
+
+pub fn result_keyboard(
+    mut key_input: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+    selected_song: Res<SelectedSong>,
+    asset_server: Res<AssetServer>,
+    charts: Res<Assets<ChartAsset>>,
+) {
+    if take_key(&mut key_input, KeyCode::R) {
+        retry(&mut state, &selected_song, &asset_server, &charts);
+    } else {
+        pop_on_keys(&mut key_input, &mut state, &[KeyCode::Return, KeyCode::Escape]);
+    }
+}
+
+pub fn despawn_result(
+    mut commands: Commands,
+    query: Query<Entity, With<ResultScreen>>
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
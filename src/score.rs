@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::state::GameState;
+use crate::notes::{FontResource, InGameEntity, JudgeAccuracy, Note, NoteType, Scoreboard};
+use crate::settings::Settings;
+
+pub const MAX_SCORE: u32 = 1_000_000;
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<JudgementTotal>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame)
+                .with_system(setup_score_text)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                .with_system(update_score_text)
+            );
+    }
+}
+
+//MAX_SCORE 중 판정이 아니라 max combo 비율로 주는 몫
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ComboBonus {
+    Off,
+    Normal,
+    High,
+}
+
+impl ComboBonus {
+    pub const ALL: [ComboBonus; 3] = [ComboBonus::Off, ComboBonus::Normal, ComboBonus::High];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ComboBonus::Off => "Off",
+            ComboBonus::Normal => "Normal",
+            ComboBonus::High => "High",
+        }
+    }
+
+    fn share(&self) -> f64 {
+        match self {
+            ComboBonus::Off => 0.,
+            ComboBonus::Normal => 0.1,
+            ComboBonus::High => 0.25,
+        }
+    }
+}
+
+impl Default for ComboBonus {
+    fn default() -> Self {
+        ComboBonus::Normal
+    }
+}
+
+//이번 판의 chart에서 나오는 판정 수
+#[derive(Default)]
+pub struct JudgementTotal(pub usize);
+
+#[derive(Component)]
+pub struct ScoreText;
+
+//판정 하나의 점수. Perfect가 100
+pub fn weight(accuracy: JudgeAccuracy) -> usize {
+    match accuracy {
+        JudgeAccuracy::Perfect => 100,
+        JudgeAccuracy::Great => 90,
+        JudgeAccuracy::Good => 60,
+        JudgeAccuracy::Bad => 10,
+        JudgeAccuracy::Miss | JudgeAccuracy::None => 0,
+    }
+}
+
+fn points(scoreboard: &Scoreboard) -> usize {
+    scoreboard.perfect * weight(JudgeAccuracy::Perfect) +
+        scoreboard.great * weight(JudgeAccuracy::Great) +
+        scoreboard.good * weight(JudgeAccuracy::Good) +
+        scoreboard.bad * weight(JudgeAccuracy::Bad)
+}
+
+//지금까지 판정된 note만으로 계산한 정확도(%). 판정이 없으면 0
+pub fn accuracy(scoreboard: &Scoreboard) -> f32 {
+    let judged = scoreboard.judged();
+    if judged == 0 {
+        return 0.;
+    }
+    points(scoreboard) as f32 / (judged * 100) as f32 * 100.
+}
+
+//total은 chart 전체의 판정 수. 모두 Perfect이고 combo가 끊기지 않으면 MAX_SCORE
+//아직 판정되지 않은 note는 0점으로 계산하므로 플레이 중에는 점수가 0부터 올라감
+pub fn score(scoreboard: &Scoreboard, total: usize, bonus: ComboBonus) -> u32 {
+    if total == 0 {
+        return 0;
+    }
+    let judge_ratio = points(scoreboard) as f64 / (total * 100) as f64;
+    let combo_ratio = scoreboard.max_combo as f64 / total as f64;
+    let share = bonus.share();
+    let score = MAX_SCORE as f64 * ((1. - share) * judge_ratio + share * combo_ratio);
+    (score.round() as u32).min(MAX_SCORE)
+}
+
+//long note는 누를 때와 뗄 때 두 번 판정. 머리를 놓쳐도 끝은 Miss로 판정됨
+pub fn judgement_total<'a>(notes: impl IntoIterator<Item = &'a Note>) -> usize {
+    notes.into_iter()
+        .map(|note| match note.note_type {
+            NoteType::Long => 2,
+            NoteType::Short => 1,
+        })
+        .sum()
+}
+
+//1234567 -> "1,234,567"
+pub fn format_score(score: u32) -> String {
+    let digits = score.to_string();
+    let mut formatted = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+pub fn setup_score_text(
+    mut commands: Commands,
+    font_resource: Res<FontResource>,
+) {
+    commands.spawn_bundle(Text2dBundle {
+        transform: Transform::from_translation(Vec3::new(300., 420., 4.)),
+        text: Text::from_section(
+            format_score(0),
+            TextStyle {
+                font: font_resource.font.clone(),
+                font_size: 30.,
+                color: Color::rgba(0.98, 0.92, 0.92, 0.8)
+            }
+        ).with_alignment(TextAlignment::CENTER),
+        ..Default::default()
+    }).insert(InGameEntity).insert(ScoreText);
+}
+
+pub fn update_score_text(
+    mut text_query: Query<&mut Text, With<ScoreText>>,
+    scoreboard_query: Query<&Scoreboard>,
+    total: Res<JudgementTotal>,
+    settings: Res<Settings>,
+) {
+    let scoreboard = match scoreboard_query.get_single() {
+        Ok(scoreboard) => scoreboard,
+        Err(_) => return,
+    };
+    let score = score(scoreboard, total.0, settings.combo_bonus);
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format_score(score);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scoreboard(perfect: usize, great: usize, good: usize, bad: usize, miss: usize, max_combo: usize) -> Scoreboard {
+        Scoreboard {
+            perfect,
+            great,
+            good,
+            bad,
+            miss,
+            max_combo,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn all_perfect_is_max_score() {
+        for bonus in ComboBonus::ALL {
+            assert_eq!(score(&scoreboard(100, 0, 0, 0, 0, 100), 100, bonus), MAX_SCORE);
+        }
+    }
+
+    #[test]
+    fn empty_chart_scores_zero() {
+        assert_eq!(score(&scoreboard(0, 0, 0, 0, 0, 0), 0, ComboBonus::Normal), 0);
+        assert_eq!(accuracy(&scoreboard(0, 0, 0, 0, 0, 0)), 0.);
+    }
+
+    #[test]
+    fn score_without_combo_bonus_follows_weights() {
+        //Great 90 + Good 60 + Bad 10 + Miss 0 = 160 / 400
+        let board = scoreboard(0, 1, 1, 1, 1, 1);
+        assert_eq!(score(&board, 4, ComboBonus::Off), 400_000);
+    }
+
+    #[test]
+    fn combo_bonus_rewards_max_combo() {
+        //판정은 같고 max combo만 다름
+        let broken = scoreboard(9, 0, 0, 0, 1, 5);
+        let unbroken = scoreboard(9, 0, 0, 0, 1, 9);
+        assert_eq!(score(&broken, 10, ComboBonus::Off), score(&unbroken, 10, ComboBonus::Off));
+        assert_eq!(score(&broken, 10, ComboBonus::Normal), 860_000);
+        assert_eq!(score(&unbroken, 10, ComboBonus::Normal), 900_000);
+        assert_eq!(score(&unbroken, 10, ComboBonus::High), 900_000);
+        assert!(score(&broken, 10, ComboBonus::High) < score(&broken, 10, ComboBonus::Normal));
+    }
+
+    #[test]
+    fn unjudged_notes_score_nothing() {
+        assert_eq!(score(&scoreboard(5, 0, 0, 0, 0, 5), 10, ComboBonus::Off), 500_000);
+    }
+
+    #[test]
+    fn accuracy_counts_bad() {
+        let board = scoreboard(1, 0, 0, 1, 0, 2);
+        assert!((accuracy(&board) - 55.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn format_score_groups_thousands() {
+        assert_eq!(format_score(0), "0");
+        assert_eq!(format_score(999), "999");
+        assert_eq!(format_score(1000), "1,000");
+        assert_eq!(format_score(MAX_SCORE), "1,000,000");
+    }
+}
@@ -0,0 +1,73 @@
+//time(ms)부터 다음 scroll point까지 note가 흐르는 속도 배율
+//0이면 멈추고 음수면 거꾸로 흐름
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScrollPoint {
+    pub time: f32,
+    pub multiplier: f32,
+}
+
+//SV(scroll velocity) 구간들. 첫 scroll point 이전은 배율 1
+//note 위치는 시간이 아니라 배율을 적분한 scroll 거리의 차이로 계산
+#[derive(Clone, Debug, Default)]
+pub struct ScrollMap {
+    points: Vec<ScrollPoint>,
+    //각 scroll point에서의 scroll 거리(ms 단위)
+    start_positions: Vec<f32>,
+}
+
+impl ScrollMap {
+    pub fn new(mut points: Vec<ScrollPoint>) -> Self {
+        points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+        let mut start_positions = Vec::with_capacity(points.len());
+        for (index, point) in points.iter().enumerate() {
+            let position = match index {
+                0 => point.time,
+                _ => {
+                    let previous = &points[index - 1];
+                    start_positions[index - 1] + (point.time - previous.time) * previous.multiplier
+                },
+            };
+            start_positions.push(position);
+        }
+        ScrollMap { points, start_positions }
+    }
+
+    //0ms부터 ms까지 흐른 scroll 거리. 배율이 항상 1이면 ms와 같음
+    pub fn position(&self, ms: f32) -> f32 {
+        match self.points.iter().rposition(|point| point.time <= ms) {
+            Some(index) => {
+                let point = &self.points[index];
+                self.start_positions[index] + (ms - point.time) * point.multiplier
+            },
+            None => ms,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(time: f32, multiplier: f32) -> ScrollPoint {
+        ScrollPoint { time, multiplier }
+    }
+
+    #[test]
+    fn no_points_is_plain_time() {
+        let scroll = ScrollMap::default();
+        assert_eq!(scroll.position(0.), 0.);
+        assert_eq!(scroll.position(1234.), 1234.);
+    }
+
+    #[test]
+    fn position_integrates_multipliers() {
+        //1000ms부터 2배, 2000ms부터 멈춤, 3000ms부터 거꾸로
+        let scroll = ScrollMap::new(vec![point(3000., -1.), point(1000., 2.), point(2000., 0.)]);
+        //첫 scroll point 이전은 배율 1
+        assert_eq!(scroll.position(500.), 500.);
+        assert_eq!(scroll.position(1500.), 2000.);
+        assert_eq!(scroll.position(2000.), 3000.);
+        assert_eq!(scroll.position(2500.), 3000.);
+        assert_eq!(scroll.position(3500.), 2500.);
+    }
+}
@@ -1,16 +1,78 @@
 use bevy::prelude::*;
 use bevy::ui::FocusPolicy;
-use crate::state::GameState;
+use bevy::input::mouse::{MouseWheel, MouseScrollUnit};
+use crate::state::{GameState, take_key, pop_on_keys};
 use crate::notes::FontResource;
+use crate::chart::ChartAsset;
+use crate::library::{SongLibrary, SelectedSong, SongSort};
+
+const SONG_BUTTON_WIDTH: f32 = 500.;
+const SONG_BUTTON_HEIGHT: f32 = 60.;
+const SONG_BUTTON_MARGIN: f32 = 5.;
+//목록이 보이는 영역의 높이. 이보다 길면 scroll
+const SONG_LIST_HEIGHT: f32 = 700.;
+const SCROLL_LINE_HEIGHT: f32 = 20.;
+const DIFFICULTY_BUTTON_HEIGHT: f32 = 40.;
+const DIFFICULTY_COLOR: Color = Color::rgba(0.2, 0.2, 0.2, 0.8);
+const DIFFICULTY_FOCUS_COLOR: Color = Color::rgba(0.9, 0.55, 0.2, 0.9);
+const STRAIN_GRAPH_HEIGHT: f32 = 60.;
+const STRAIN_COLOR: Color = Color::rgba(0.9, 0.55, 0.2, 0.6);
+
 pub struct SelectMenuPlugin;
 
 #[derive(Component)]
 pub struct MusicList;
 
+//SongLibrary.songs의 index
+#[derive(Component)]
+pub struct SongButton(usize);
+
+#[derive(Component)]
+pub struct SongButtonText(usize);
+
+//focus된 곡의 난이도 목록
+#[derive(Component)]
+pub struct DifficultyBar;
+
+//SongInfo.charts의 index
+#[derive(Component)]
+pub struct DifficultyButton(usize);
+
+//focus된 chart의 구간별 strain
+#[derive(Component)]
+pub struct StrainGraph;
+
+#[derive(Component)]
+pub struct StrainBar;
+
+//위로 scroll된 거리(px). 0 ~ -(목록 높이 - SONG_LIST_HEIGHT)
+#[derive(Component, Default)]
+pub struct ScrollingList {
+    position: f32,
+}
+
+//키보드나 마우스로 현재 선택된 곡과 난이도. 게임을 마치고 돌아와도 유지
+#[derive(Default)]
+pub struct SongFocus {
+    pub song: usize,
+    pub difficulty: usize,
+}
+
+impl SongFocus {
+    //곡이 바뀌면 가장 쉬운 난이도부터
+    fn set_song(&mut self, song: usize) {
+        if self.song != song {
+            self.song = song;
+            self.difficulty = 0;
+        }
+    }
+}
+
 impl Plugin for SelectMenuPlugin {
     fn build(&self, app: &mut App) {
         app
             .init_resource::<MenuResource>()
+            .init_resource::<SongFocus>()
             .add_system_set(
                 SystemSet::on_enter(GameState::SelectMenu)
                 .with_system(setup_menu)
@@ -22,6 +84,16 @@ impl Plugin for SelectMenuPlugin {
             .add_system_set(
                 SystemSet::on_update(GameState::SelectMenu)
                 .with_system(music_button_interaction)
+                .with_system(keyboard_navigation)
+                .with_system(mouse_scroll)
+                .with_system(scroll_to_focus)
+                .with_system(update_song_button_image)
+                .with_system(update_song_button_text)
+                .with_system(update_difficulty_bar)
+                .with_system(difficulty_button_interaction)
+                .with_system(update_difficulty_button_color)
+                .with_system(update_strain_graph)
+                .with_system(change_song_sort)
             )
             .add_system_set(
                 SystemSet::on_pause(GameState::SelectMenu)
@@ -34,12 +106,6 @@ impl Plugin for SelectMenuPlugin {
         }
 }
 
-pub struct SongInfo {
-    name: String,
-    time: f32,
-    difficult: f32,
-}
-
 pub struct MenuResource {
     music_button: Handle<Image>,
     music_hover: Handle<Image>,
@@ -64,16 +130,92 @@ impl FromWorld for MenuResource {
 pub fn setup_menu(
     mut commands: Commands,
     font_resource: Res<FontResource>,
-    button_resource: Res<MenuResource>
+    button_resource: Res<MenuResource>,
+    library: Res<SongLibrary>,
+    focus: Res<SongFocus>,
+) {
+    let scrolling_list = ScrollingList {
+        position: scroll_position(focus.song, library.songs.len(), 0.),
+    };
+
+    commands.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        //목록이 보이는 영역. 밖으로 나간 곡은 가려짐
+        parent.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(SONG_BUTTON_WIDTH + 2. * SONG_BUTTON_MARGIN), Val::Px(SONG_LIST_HEIGHT)),
+                flex_direction: FlexDirection::ColumnReverse,
+                overflow: Overflow::Hidden,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        }).with_children(|parent| {
+            //scroll에 따라 움직이는 panel
+            parent.spawn_bundle(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::ColumnReverse,
+                    flex_grow: 1.,
+                    max_size: Size::new(Val::Undefined, Val::Undefined),
+                    position: UiRect { top: Val::Px(scrolling_list.position), ..Default::default() },
+                    ..Default::default()
+                },
+                color: Color::NONE.into(),
+                ..Default::default()
+            }).with_children(|parent| {
+                for (index, song) in library.songs.iter().enumerate() {
+                    spawn_song_button(parent, &font_resource, &button_resource, index, &song_button_text(&song.name, song.time));
+                }
+            }).insert(scrolling_list);
+        });
+
+        //focus된 곡의 난이도. update_difficulty_bar에서 채움
+        parent.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(SONG_BUTTON_WIDTH), Val::Px(DIFFICULTY_BUTTON_HEIGHT)),
+                margin: UiRect::all(Val::Px(SONG_BUTTON_MARGIN)),
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        }).insert(DifficultyBar);
+
+        //update_strain_graph에서 채움
+        parent.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(SONG_BUTTON_WIDTH), Val::Px(STRAIN_GRAPH_HEIGHT)),
+                margin: UiRect::all(Val::Px(SONG_BUTTON_MARGIN)),
+                align_items: AlignItems::FlexStart,
+                ..Default::default()
+            },
+            color: Color::rgba(0.1, 0.1, 0.1, 0.5).into(),
+            ..Default::default()
+        }).insert(StrainGraph);
+    }).insert(MusicList);
+}
+
+fn spawn_song_button(
+    parent: &mut ChildBuilder,
+    font_resource: &FontResource,
+    button_resource: &MenuResource,
+    index: usize,
+    text: &str,
 ) {
-    commands.spawn_bundle( ButtonBundle {
+    parent.spawn_bundle( ButtonBundle {
         style: Style {
-            align_self: AlignSelf::FlexEnd,
-            align_items: AlignItems::FlexEnd,
-            justify_content : JustifyContent::Center,
-            position: UiRect::new(Val::Px(250.), Val::Auto, Val::Auto, Val::Auto),
-            size: Size::new(Val::Px(500.0), Val::Px(60.0)),
-            margin: UiRect::all(Val::Auto),
+            size: Size::new(Val::Px(SONG_BUTTON_WIDTH), Val::Px(SONG_BUTTON_HEIGHT)),
+            min_size: Size::new(Val::Px(SONG_BUTTON_WIDTH), Val::Px(SONG_BUTTON_HEIGHT)),
+            margin: UiRect::all(Val::Px(SONG_BUTTON_MARGIN)),
             ..Default::default()
         },
         color: Color::NONE.into(),
@@ -91,7 +233,7 @@ pub fn setup_menu(
         }).insert(FocusPolicy::Pass).with_children(|parent| {
             parent.spawn_bundle( TextBundle {
                 text: Text::from_section(
-                    "PUPA",
+                    text,
                     TextStyle {
                         font: font_resource.font.clone(),
                         font_size: 30.,
@@ -99,40 +241,321 @@ pub fn setup_menu(
                     }),
                 focus_policy: FocusPolicy::Pass,
                 ..Default::default()
-            });
+            }).insert(SongButtonText(index));
         });
-    }).insert(MusicList);
+    }).insert(SongButton(index));
+}
+
+fn song_button_text(name: &str, time: f32) -> String {
+    let time = time as u32;
+    format!("{}  {}:{:02}", name, time / 60, time % 60)
+}
+
+//index번째 곡이 보이도록 하는 ScrollingList.position. 이미 보이면 current를 그대로 반환
+fn scroll_position(index: usize, song_count: usize, current: f32) -> f32 {
+    let item_height = SONG_BUTTON_HEIGHT + 2. * SONG_BUTTON_MARGIN;
+    let max_scroll = (song_count as f32 * item_height - SONG_LIST_HEIGHT).max(0.);
+    let item_top = index as f32 * item_height;
+    let mut offset = -current;
+
+    if item_top < offset {
+        offset = item_top;
+    } else if item_top + item_height > offset + SONG_LIST_HEIGHT {
+        offset = item_top + item_height - SONG_LIST_HEIGHT;
+    }
+    -offset.clamp(0., max_scroll)
+}
+
+fn select_song(
+    commands: &mut Commands,
+    state: &mut State<GameState>,
+    library: &SongLibrary,
+    asset_server: &AssetServer,
+    charts: &Assets<ChartAsset>,
+    focus: &SongFocus,
+) {
+    let song = &library.songs[focus.song];
+    if focus.difficulty >= song.charts.len() {
+        return;
+    }
+    let selected_song = SelectedSong::new(&song.charts[focus.difficulty]);
+    //chart가 아직 loading 중이면 무시
+    match selected_song.get(asset_server, charts) {
+        Some(Ok(_)) => state.push(GameState::InGame).unwrap(),
+        Some(Err(_)) => state.push(GameState::ChartError).unwrap(),
+        None => return,
+    }
+    commands.insert_resource(selected_song);
 }
 
 pub fn music_button_interaction(
-    interaction_query: Query<(&Children, &Interaction), Changed<Interaction>>,
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &SongButton), Changed<Interaction>>,
     mut state: ResMut<State<GameState>>,
-    mut image_query: Query<&mut UiImage>,
-    button_resource: Res<MenuResource>
+    mut focus: ResMut<SongFocus>,
+    library: Res<SongLibrary>,
+    asset_server: Res<AssetServer>,
+    charts: Res<Assets<ChartAsset>>,
 ) {
-    for (children, interaction) in interaction_query.iter() {
-        let child = children.iter().next().unwrap();
-        let mut image = image_query.get_mut(*child).unwrap();
+    for (interaction, song_button) in interaction_query.iter() {
         match interaction {
             Interaction::Clicked => {
-                *image = UiImage(button_resource.music_clicked.clone());
-                state.push(GameState::InGame).unwrap();
+                focus.set_song(song_button.0);
+                select_song(&mut commands, &mut state, &library, &asset_server, &charts, &focus);
+                return;
             },
             Interaction::Hovered => {
-                *image = UiImage(button_resource.music_hover.clone());
+                focus.set_song(song_button.0);
             },
-            Interaction::None => {
-                *image = UiImage(button_resource.music_button.clone());
+            Interaction::None => (),
+        }
+    }
+}
+
+pub fn keyboard_navigation(
+    mut commands: Commands,
+    mut key_input: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+    mut focus: ResMut<SongFocus>,
+    library: Res<SongLibrary>,
+    asset_server: Res<AssetServer>,
+    charts: Res<Assets<ChartAsset>>,
+) {
+    if pop_on_keys(&mut key_input, &mut state, &[KeyCode::Escape]) {
+        return;
+    }
+    if library.songs.is_empty() {
+        return;
+    }
+
+    if key_input.just_pressed(KeyCode::Up) && focus.song > 0 {
+        let song = focus.song - 1;
+        focus.set_song(song);
+    }
+    if key_input.just_pressed(KeyCode::Down) && focus.song + 1 < library.songs.len() {
+        let song = focus.song + 1;
+        focus.set_song(song);
+    }
+    if key_input.just_pressed(KeyCode::Left) && focus.difficulty > 0 {
+        focus.difficulty -= 1;
+    }
+    if key_input.just_pressed(KeyCode::Right) && focus.difficulty + 1 < library.songs[focus.song].charts.len() {
+        focus.difficulty += 1;
+    }
+    if take_key(&mut key_input, KeyCode::Return) {
+        select_song(&mut commands, &mut state, &library, &asset_server, &charts, &focus);
+    }
+}
+
+pub fn mouse_scroll(
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    mut list_query: Query<(&mut ScrollingList, &mut Style)>,
+    library: Res<SongLibrary>,
+) {
+    let item_height = SONG_BUTTON_HEIGHT + 2. * SONG_BUTTON_MARGIN;
+    let max_scroll = (library.songs.len() as f32 * item_height - SONG_LIST_HEIGHT).max(0.);
+
+    for mouse_wheel_event in mouse_wheel_events.iter() {
+        let dy = match mouse_wheel_event.unit {
+            MouseScrollUnit::Line => mouse_wheel_event.y * SCROLL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => mouse_wheel_event.y,
+        };
+        for (mut scrolling_list, mut style) in list_query.iter_mut() {
+            scrolling_list.position = (scrolling_list.position + dy).clamp(-max_scroll, 0.);
+            style.position.top = Val::Px(scrolling_list.position);
+        }
+    }
+}
+
+//키보드로 focus가 목록 밖으로 나가면 보이도록 scroll
+pub fn scroll_to_focus(
+    focus: Res<SongFocus>,
+    mut list_query: Query<(&mut ScrollingList, &mut Style)>,
+    library: Res<SongLibrary>,
+) {
+    if !focus.is_changed() {
+        return;
+    }
+    for (mut scrolling_list, mut style) in list_query.iter_mut() {
+        scrolling_list.position = scroll_position(focus.song, library.songs.len(), scrolling_list.position);
+        style.position.top = Val::Px(scrolling_list.position);
+    }
+}
+
+//focus된 곡은 hover image, 누르고 있는 곡은 clicked image로 표시
+pub fn update_song_button_image(
+    button_query: Query<(&Children, &Interaction, &SongButton)>,
+    mut image_query: Query<&mut UiImage>,
+    button_resource: Res<MenuResource>,
+    focus: Res<SongFocus>,
+) {
+    for (children, interaction, song_button) in button_query.iter() {
+        let child = children.iter().next().unwrap();
+        let mut image = image_query.get_mut(*child).unwrap();
+        let handle = if *interaction == Interaction::Clicked {
+            &button_resource.music_clicked
+        } else if song_button.0 == focus.song {
+            &button_resource.music_hover
+        } else {
+            &button_resource.music_button
+        };
+        if image.0 != *handle {
+            *image = UiImage(handle.clone());
+        }
+    }
+}
+
+//chart가 load되어 곡 이름이 metadata의 title로 바뀌면 반영
+pub fn update_song_button_text(
+    library: Res<SongLibrary>,
+    mut text_query: Query<(&mut Text, &SongButtonText)>,
+) {
+    if !library.is_changed() {
+        return;
+    }
+    for (mut text, song_button) in text_query.iter_mut() {
+        let song = &library.songs[song_button.0];
+        text.sections[0].value = song_button_text(&song.name, song.time);
+    }
+}
+
+//focus된 곡이 바뀌거나 chart가 load되면 난이도 목록을 다시 만듦
+pub fn update_difficulty_bar(
+    mut commands: Commands,
+    font_resource: Res<FontResource>,
+    library: Res<SongLibrary>,
+    focus: Res<SongFocus>,
+    bar_query: Query<Entity, With<DifficultyBar>>,
+    button_query: Query<Entity, With<DifficultyButton>>,
+    added_query: Query<Entity, Added<DifficultyBar>>,
+) {
+    if !library.is_changed() && !focus.is_changed() && added_query.is_empty() {
+        return;
+    }
+    let song = match library.songs.get(focus.song) {
+        Some(song) => song,
+        None => return,
+    };
+
+    for entity in button_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for bar in bar_query.iter() {
+        commands.entity(bar).with_children(|parent| {
+            for (index, chart_info) in song.charts.iter().enumerate() {
+                parent.spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Auto, Val::Percent(100.)),
+                        padding: UiRect::new(Val::Px(10.), Val::Px(10.), Val::Px(0.), Val::Px(0.)),
+                        margin: UiRect::new(Val::Px(5.), Val::Px(5.), Val::Px(0.), Val::Px(0.)),
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    color: DIFFICULTY_COLOR.into(),
+                    ..Default::default()
+                }).with_children(|parent| {
+                    parent.spawn_bundle(TextBundle {
+                        text: Text::from_section(
+                            format!("{} {}  {:.2}*", chart_info.difficulty, chart_info.level, chart_info.stars),
+                            TextStyle {
+                                font: font_resource.font.clone(),
+                                font_size: 20.,
+                                color: Color::rgba(0.9, 0.9, 0.9, 1.)
+                            }),
+                        focus_policy: FocusPolicy::Pass,
+                        ..Default::default()
+                    });
+                }).insert(DifficultyButton(index));
             }
+        });
+    }
+}
+
+pub fn difficulty_button_interaction(
+    interaction_query: Query<(&Interaction, &DifficultyButton), Changed<Interaction>>,
+    mut focus: ResMut<SongFocus>,
+) {
+    for (interaction, difficulty_button) in interaction_query.iter() {
+        if *interaction == Interaction::Clicked {
+            focus.difficulty = difficulty_button.0;
         }
     }
 }
 
+pub fn update_difficulty_button_color(
+    mut button_query: Query<(&mut UiColor, &DifficultyButton)>,
+    focus: Res<SongFocus>,
+) {
+    for (mut color, difficulty_button) in button_query.iter_mut() {
+        color.0 = if difficulty_button.0 == focus.difficulty {
+            DIFFICULTY_FOCUS_COLOR
+        } else {
+            DIFFICULTY_COLOR
+        };
+    }
+}
+
+pub fn update_strain_graph(
+    mut commands: Commands,
+    library: Res<SongLibrary>,
+    focus: Res<SongFocus>,
+    graph_query: Query<Entity, With<StrainGraph>>,
+    bar_query: Query<Entity, With<StrainBar>>,
+    added_query: Query<Entity, Added<StrainGraph>>,
+) {
+    if !library.is_changed() && !focus.is_changed() && added_query.is_empty() {
+        return;
+    }
+    let chart_info = match library.songs.get(focus.song).and_then(|song| song.charts.get(focus.difficulty)) {
+        Some(chart_info) => chart_info,
+        None => return,
+    };
+
+    for entity in bar_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let max_strain = chart_info.strain.iter().cloned().fold(0., f32::max);
+    if max_strain <= 0. {
+        return;
+    }
+    let bar_width = SONG_BUTTON_WIDTH / chart_info.strain.len() as f32;
+    for graph in graph_query.iter() {
+        commands.entity(graph).with_children(|parent| {
+            for strain in chart_info.strain.iter() {
+                parent.spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(bar_width), Val::Percent(strain / max_strain * 100.)),
+                        ..Default::default()
+                    },
+                    color: STRAIN_COLOR.into(),
+                    ..Default::default()
+                }).insert(StrainBar);
+            }
+        });
+    }
+}
+
+//Tab으로 이름순/난이도순 정렬 변경
+pub fn change_song_sort(
+    key_input: Res<Input<KeyCode>>,
+    mut library: ResMut<SongLibrary>,
+    mut focus: ResMut<SongFocus>,
+) {
+    if !key_input.just_pressed(KeyCode::Tab) || library.songs.is_empty() {
+        return;
+    }
+    let sort = match library.sort {
+        SongSort::Name => SongSort::Difficulty,
+        SongSort::Difficulty => SongSort::Name,
+    };
+    library.sort_keeping_focus(sort, &mut focus);
+}
+
 pub fn despawn_music_button(
     mut commands: Commands,
-    button_query: Query<(Entity, &Button)>
+    list_query: Query<Entity, With<MusicList>>
 ) {
-    for (entity, button) in button_query.iter() {
+    for entity in list_query.iter() {
         commands.entity(entity).despawn_recursive()
     }
-}
\ No newline at end of file
+}
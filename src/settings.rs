@@ -0,0 +1,137 @@
+use std::fs;
+use std::path::PathBuf;
+use bevy::prelude::*;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use crate::key_bindings::KeyBindings;
+use crate::judge::{JudgePreset, EarlyLateDisplay};
+use crate::score::ComboBonus;
+
+const CONFIG_FOLDER: &str = "rhythme";
+const SETTINGS_FILE: &str = "settings.ron";
+//chart에 속도 정보가 없을 때 쓰는 note 속도
+pub const DEFAULT_NOTE_SPEED: f32 = 17.4;
+//note 속도에 곱하는 scroll speed 배율의 범위와 한 번에 바뀌는 값
+const SCROLL_SPEED_STEP: f32 = 0.1;
+const MIN_SCROLL_SPEED: f32 = 0.5;
+const MAX_SCROLL_SPEED: f32 = 4.;
+
+//플랫폼 config 폴더의 settings.ron에 저장되는 사용자 설정
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub volume: f64,
+    pub note_speed: f32,
+    pub scroll_speed: f32,
+    //ms. 소리가 늦게 들리는 만큼 판정 시각을 늦춤
+    pub audio_offset: i32,
+    //ms. 화면이 늦게 표시되는 만큼 note를 앞당겨 그림
+    pub visual_offset: i32,
+    pub judge_preset: JudgePreset,
+    pub early_late: EarlyLateDisplay,
+    pub combo_bonus: ComboBonus,
+    pub window_width: f32,
+    pub window_height: f32,
+    pub key_bindings: KeyBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            volume: 0.1,
+            note_speed: DEFAULT_NOTE_SPEED,
+            scroll_speed: 1.,
+            audio_offset: 0,
+            visual_offset: 0,
+            judge_preset: JudgePreset::Standard,
+            early_late: EarlyLateDisplay::NonPerfect,
+            combo_bonus: ComboBonus::Normal,
+            window_width: 1000.,
+            window_height: 1000.,
+            key_bindings: KeyBindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    //direction이 1이면 scroll speed를 올리고 -1이면 내림
+    pub fn step_scroll_speed(&mut self, direction: i32) {
+        let scroll_speed = self.scroll_speed + SCROLL_SPEED_STEP * direction as f32;
+        self.scroll_speed = ((scroll_speed / SCROLL_SPEED_STEP).round() * SCROLL_SPEED_STEP)
+            .clamp(MIN_SCROLL_SPEED, MAX_SCROLL_SPEED);
+    }
+
+    //MusicTimer의 elapsed(초)를 note timing과 비교할 판정 시각으로 변환
+    pub fn judge_time(&self, elapsed: f32) -> f32 {
+        elapsed - self.audio_offset as f32 / 1000.
+    }
+
+    //MusicTimer의 elapsed(초)를 note 위치 계산에 쓰는 시각으로 변환
+    //note가 판정 시각에 판정선을 지나도록 판정 시각에 visual offset만 더함
+    pub fn draw_time(&self, elapsed: f32) -> f32 {
+        self.judge_time(elapsed) + self.visual_offset as f32 / 1000.
+    }
+
+    //설정 파일이 없거나 읽을 수 없으면 기본값을 사용
+    //window 크기에 필요해서 DefaultPlugins보다 먼저 읽으므로 읽지 못한 이유는 log 대신 함께 반환
+    pub fn load() -> (Self, SettingsLoadError) {
+        match load_config(SETTINGS_FILE) {
+            Ok(settings) => (settings.unwrap_or_default(), SettingsLoadError(None)),
+            Err(error) => (Settings::default(), SettingsLoadError(Some(error))),
+        }
+    }
+
+    pub fn save(&self) {
+        save_config(SETTINGS_FILE, self);
+    }
+}
+
+//Settings::load에서 설정 파일을 읽지 못한 이유
+pub struct SettingsLoadError(pub Option<String>);
+
+//log가 준비된 후에 출력하기 위해 startup system으로 실행
+pub fn report_settings_error(load_error: Res<SettingsLoadError>) {
+    if let Some(error) = &load_error.0 {
+        warn!("{}, using default settings", error);
+    }
+}
+
+//config 폴더의 ron 파일을 읽음. 파일이 없거나 열 수 없으면 None
+pub fn load_config<T: DeserializeOwned>(file: &str) -> Result<Option<T>, String> {
+    let path = match config_path(file) {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    let string = match fs::read_to_string(&path) {
+        Ok(string) => string,
+        Err(_) => return Ok(None),
+    };
+    ron::from_str(&string)
+        .map(Some)
+        .map_err(|error| format!("failed to parse {}: {}", path.display(), error))
+}
+
+pub fn save_config<T: Serialize>(file: &str, value: &T) {
+    let path = match config_path(file) {
+        Some(path) => path,
+        None => {
+            warn!("no config directory to save {}", file);
+            return;
+        }
+    };
+    let result = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|string| {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+            }
+            fs::write(&path, string).map_err(|error| error.to_string())
+        });
+    if let Err(error) = result {
+        warn!("failed to save {}: {}", path.display(), error);
+    }
+}
+
+fn config_path(file: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(CONFIG_FOLDER).join(file))
+}
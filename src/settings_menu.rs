@@ -0,0 +1,332 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use crate::state::{GameState, take_key, pop_on_keys};
+use crate::notes::FontResource;
+use crate::settings::Settings;
+use crate::judge::{JudgePreset, EarlyLateDisplay};
+use crate::score::ComboBonus;
+
+const TEXT_COLOR: Color = Color::rgba(0.9, 0.9, 0.9, 1.);
+const BUTTON_COLOR: Color = Color::rgba(0.2, 0.2, 0.2, 0.8);
+const BUTTON_FOCUS_COLOR: Color = Color::rgba(0.9, 0.55, 0.2, 0.9);
+const VOLUME_STEP: f64 = 0.05;
+const NOTE_SPEED_STEP: f32 = 0.5;
+const MIN_NOTE_SPEED: f32 = 5.;
+const MAX_NOTE_SPEED: f32 = 40.;
+//audio/visual offset(ms)
+const OFFSET_STEP: i32 = 5;
+const MAX_OFFSET: i32 = 300;
+//선택할 수 있는 창 크기
+const WINDOW_SIZES: [(f32, f32); 5] = [
+    (800., 800.),
+    (1000., 1000.),
+    (1200., 1200.),
+    (1280., 720.),
+    (1920., 1080.),
+];
+
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<SettingsFocus>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Settings)
+                .with_system(setup_settings_menu)
+            )
+            .add_system_set(
+                SystemSet::on_resume(GameState::Settings)
+                .with_system(setup_settings_menu)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Settings)
+                .with_system(settings_button_interaction)
+                .with_system(settings_keyboard)
+                .with_system(update_settings_button_color)
+                .with_system(update_settings_button_text)
+            )
+            .add_system_set(
+                SystemSet::on_pause(GameState::Settings)
+                .with_system(despawn_settings_menu)
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Settings)
+                .with_system(despawn_settings_menu)
+                .with_system(save_settings)
+            );
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum SettingsButton {
+    Volume,
+    NoteSpeed,
+    ScrollSpeed,
+    AudioOffset,
+    VisualOffset,
+    Calibrate,
+    JudgePreset,
+    EarlyLate,
+    ComboBonus,
+    WindowSize,
+    KeyBindings,
+    Back,
+}
+
+//위에서부터 표시되는 순서
+const SETTINGS_BUTTONS: [SettingsButton; 12] = [
+    SettingsButton::Volume,
+    SettingsButton::NoteSpeed,
+    SettingsButton::ScrollSpeed,
+    SettingsButton::AudioOffset,
+    SettingsButton::VisualOffset,
+    SettingsButton::Calibrate,
+    SettingsButton::JudgePreset,
+    SettingsButton::EarlyLate,
+    SettingsButton::ComboBonus,
+    SettingsButton::WindowSize,
+    SettingsButton::KeyBindings,
+    SettingsButton::Back,
+];
+
+//SETTINGS_BUTTONS의 index
+#[derive(Default)]
+pub struct SettingsFocus(usize);
+
+#[derive(Component)]
+pub struct SettingsMenu;
+
+#[derive(Component)]
+pub struct SettingsButtonText(SettingsButton);
+
+pub fn setup_settings_menu(
+    mut commands: Commands,
+    font_resource: Res<FontResource>,
+) {
+    commands.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        parent.spawn_bundle(TextBundle {
+            style: Style {
+                margin: UiRect::all(Val::Px(30.)),
+                ..Default::default()
+            },
+            text: Text::from_section(
+                "Settings",
+                TextStyle {
+                    font: font_resource.font.clone(),
+                    font_size: 50.,
+                    color: TEXT_COLOR,
+                }),
+            ..Default::default()
+        });
+
+        for button in SETTINGS_BUTTONS.iter() {
+            parent.spawn_bundle(ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(400.), Val::Px(38.)),
+                    margin: UiRect::all(Val::Px(3.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                color: BUTTON_COLOR.into(),
+                ..Default::default()
+            }).with_children(|parent| {
+                parent.spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font_resource.font.clone(),
+                            font_size: 26.,
+                            color: TEXT_COLOR,
+                        }),
+                    focus_policy: FocusPolicy::Pass,
+                    ..Default::default()
+                }).insert(SettingsButtonText(*button));
+            }).insert(*button);
+        }
+
+        parent.spawn_bundle(TextBundle {
+            style: Style {
+                margin: UiRect::all(Val::Px(20.)),
+                ..Default::default()
+            },
+            text: Text::from_section(
+                "Left/Right to change, Esc to save and go back",
+                TextStyle {
+                    font: font_resource.font.clone(),
+                    font_size: 18.,
+                    color: TEXT_COLOR,
+                }),
+            ..Default::default()
+        });
+    }).insert(SettingsMenu);
+}
+
+fn window_size_index(settings: &Settings) -> usize {
+    WINDOW_SIZES.iter()
+        .position(|&(width, height)| width == settings.window_width && height == settings.window_height)
+        .unwrap_or(1)
+}
+
+//direction이 1이면 다음 값, -1이면 이전 값. 창 크기는 목록을 순환
+fn change_value(button: SettingsButton, direction: i32, settings: &mut Settings, windows: &mut Windows) {
+    match button {
+        SettingsButton::Volume => {
+            let volume = settings.volume + VOLUME_STEP * direction as f64;
+            settings.volume = (volume / VOLUME_STEP).round().clamp(0., 1. / VOLUME_STEP) * VOLUME_STEP;
+        },
+        SettingsButton::NoteSpeed => {
+            let note_speed = settings.note_speed + NOTE_SPEED_STEP * direction as f32;
+            settings.note_speed = note_speed.clamp(MIN_NOTE_SPEED, MAX_NOTE_SPEED);
+        },
+        SettingsButton::ScrollSpeed => settings.step_scroll_speed(direction),
+        SettingsButton::AudioOffset => {
+            settings.audio_offset = (settings.audio_offset + OFFSET_STEP * direction).clamp(-MAX_OFFSET, MAX_OFFSET);
+        },
+        SettingsButton::VisualOffset => {
+            settings.visual_offset = (settings.visual_offset + OFFSET_STEP * direction).clamp(-MAX_OFFSET, MAX_OFFSET);
+        },
+        SettingsButton::JudgePreset => {
+            let count = JudgePreset::ALL.len() as i32;
+            let index = JudgePreset::ALL.iter().position(|&preset| preset == settings.judge_preset).unwrap() as i32;
+            settings.judge_preset = JudgePreset::ALL[(index + direction).rem_euclid(count) as usize];
+        },
+        SettingsButton::EarlyLate => {
+            let count = EarlyLateDisplay::ALL.len() as i32;
+            let index = EarlyLateDisplay::ALL.iter().position(|&display| display == settings.early_late).unwrap() as i32;
+            settings.early_late = EarlyLateDisplay::ALL[(index + direction).rem_euclid(count) as usize];
+        },
+        SettingsButton::ComboBonus => {
+            let count = ComboBonus::ALL.len() as i32;
+            let index = ComboBonus::ALL.iter().position(|&bonus| bonus == settings.combo_bonus).unwrap() as i32;
+            settings.combo_bonus = ComboBonus::ALL[(index + direction).rem_euclid(count) as usize];
+        },
+        SettingsButton::WindowSize => {
+            let count = WINDOW_SIZES.len() as i32;
+            let index = (window_size_index(settings) as i32 + direction).rem_euclid(count) as usize;
+            let (width, height) = WINDOW_SIZES[index];
+            settings.window_width = width;
+            settings.window_height = height;
+            if let Some(window) = windows.get_primary_mut() {
+                window.set_resolution(width, height);
+            }
+        },
+        SettingsButton::Calibrate | SettingsButton::KeyBindings | SettingsButton::Back => (),
+    }
+}
+
+fn activate(button: SettingsButton, state: &mut State<GameState>, settings: &mut Settings, windows: &mut Windows) {
+    match button {
+        SettingsButton::Calibrate => state.push(GameState::Calibration).unwrap(),
+        SettingsButton::KeyBindings => state.push(GameState::KeyBindings).unwrap(),
+        SettingsButton::Back => state.pop().unwrap(),
+        _ => change_value(button, 1, settings, windows),
+    }
+}
+
+pub fn settings_button_interaction(
+    interaction_query: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+    mut state: ResMut<State<GameState>>,
+    mut focus: ResMut<SettingsFocus>,
+    mut settings: ResMut<Settings>,
+    mut windows: ResMut<Windows>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        let index = SETTINGS_BUTTONS.iter().position(|settings_button| settings_button == button).unwrap();
+        match interaction {
+            Interaction::Clicked => {
+                focus.0 = index;
+                activate(*button, &mut state, &mut settings, &mut windows);
+                return;
+            },
+            Interaction::Hovered => focus.0 = index,
+            Interaction::None => (),
+        }
+    }
+}
+
+pub fn settings_keyboard(
+    mut key_input: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+    mut focus: ResMut<SettingsFocus>,
+    mut settings: ResMut<Settings>,
+    mut windows: ResMut<Windows>,
+) {
+    if pop_on_keys(&mut key_input, &mut state, &[KeyCode::Escape]) {
+        return;
+    }
+    if key_input.just_pressed(KeyCode::Up) && focus.0 > 0 {
+        focus.0 -= 1;
+    }
+    if key_input.just_pressed(KeyCode::Down) && focus.0 + 1 < SETTINGS_BUTTONS.len() {
+        focus.0 += 1;
+    }
+    if key_input.just_pressed(KeyCode::Left) {
+        change_value(SETTINGS_BUTTONS[focus.0], -1, &mut settings, &mut windows);
+    }
+    if key_input.just_pressed(KeyCode::Right) {
+        change_value(SETTINGS_BUTTONS[focus.0], 1, &mut settings, &mut windows);
+    }
+    if take_key(&mut key_input, KeyCode::Return) {
+        activate(SETTINGS_BUTTONS[focus.0], &mut state, &mut settings, &mut windows);
+    }
+}
+
+pub fn update_settings_button_color(
+    mut button_query: Query<(&mut UiColor, &SettingsButton)>,
+    focus: Res<SettingsFocus>,
+) {
+    for (mut color, button) in button_query.iter_mut() {
+        color.0 = if *button == SETTINGS_BUTTONS[focus.0] {
+            BUTTON_FOCUS_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+    }
+}
+
+pub fn update_settings_button_text(
+    mut text_query: Query<(&mut Text, &SettingsButtonText)>,
+    settings: Res<Settings>,
+) {
+    for (mut text, button_text) in text_query.iter_mut() {
+        text.sections[0].value = match button_text.0 {
+            SettingsButton::Volume => format!("Volume  < {:.0}% >", settings.volume * 100.),
+            SettingsButton::NoteSpeed => format!("Note Speed  < {:.1} >", settings.note_speed),
+            SettingsButton::ScrollSpeed => format!("Scroll Speed  < x{:.1} >", settings.scroll_speed),
+            SettingsButton::AudioOffset => format!("Audio Offset  < {:+} ms >", settings.audio_offset),
+            SettingsButton::VisualOffset => format!("Visual Offset  < {:+} ms >", settings.visual_offset),
+            SettingsButton::Calibrate => "Calibrate Audio Offset".to_string(),
+            SettingsButton::JudgePreset => format!("Judgement  < {} >", settings.judge_preset.name()),
+            SettingsButton::EarlyLate => format!("Early/Late  < {} >", settings.early_late.name()),
+            SettingsButton::ComboBonus => format!("Combo Bonus  < {} >", settings.combo_bonus.name()),
+            SettingsButton::WindowSize => format!("Window  < {} x {} >", settings.window_width, settings.window_height),
+            SettingsButton::KeyBindings => "Key Bindings".to_string(),
+            SettingsButton::Back => "Back".to_string(),
+        };
+    }
+}
+
+pub fn save_settings(settings: Res<Settings>) {
+    settings.save();
+}
+
+pub fn despawn_settings_menu(
+    mut commands: Commands,
+    query: Query<Entity, With<SettingsMenu>>
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,193 @@
+use bevy::prelude::*;
+use bevy::app::AppExit;
+use bevy::ui::FocusPolicy;
+use crate::state::{GameState, take_key};
+use crate::notes::FontResource;
+
+const TEXT_COLOR: Color = Color::rgba(0.9, 0.9, 0.9, 1.);
+const BUTTON_COLOR: Color = Color::rgba(0.2, 0.2, 0.2, 0.8);
+const BUTTON_FOCUS_COLOR: Color = Color::rgba(0.9, 0.55, 0.2, 0.9);
+
+pub struct StartMenuPlugin;
+
+impl Plugin for StartMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<StartMenuFocus>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::StartMenu)
+                .with_system(setup_start_menu)
+            )
+            .add_system_set(
+                SystemSet::on_resume(GameState::StartMenu)
+                .with_system(setup_start_menu)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::StartMenu)
+                .with_system(start_button_interaction)
+                .with_system(start_menu_keyboard)
+                .with_system(update_start_button_color)
+            )
+            .add_system_set(
+                SystemSet::on_pause(GameState::StartMenu)
+                .with_system(despawn_start_menu)
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::StartMenu)
+                .with_system(despawn_start_menu)
+            );
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum StartButton {
+    Play,
+    Settings,
+    Records,
+    Quit,
+}
+
+//위에서부터 표시되는 순서
+const START_BUTTONS: [(StartButton, &str); 4] = [
+    (StartButton::Play, "Play"),
+    (StartButton::Settings, "Settings"),
+    (StartButton::Records, "Records"),
+    (StartButton::Quit, "Quit"),
+];
+
+//START_BUTTONS의 index
+#[derive(Default)]
+pub struct StartMenuFocus(usize);
+
+#[derive(Component)]
+pub struct StartMenu;
+
+pub fn setup_start_menu(
+    mut commands: Commands,
+    font_resource: Res<FontResource>,
+) {
+    commands.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    }).with_children(|parent| {
+        parent.spawn_bundle(TextBundle {
+            style: Style {
+                margin: UiRect::all(Val::Px(40.)),
+                ..Default::default()
+            },
+            text: Text::from_section(
+                "rhythme",
+                TextStyle {
+                    font: font_resource.font.clone(),
+                    font_size: 80.,
+                    color: TEXT_COLOR,
+                }),
+            ..Default::default()
+        });
+
+        for (button, text) in START_BUTTONS.iter() {
+            parent.spawn_bundle(ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(300.), Val::Px(60.)),
+                    margin: UiRect::all(Val::Px(8.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                color: BUTTON_COLOR.into(),
+                ..Default::default()
+            }).with_children(|parent| {
+                parent.spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        *text,
+                        TextStyle {
+                            font: font_resource.font.clone(),
+                            font_size: 30.,
+                            color: TEXT_COLOR,
+                        }),
+                    focus_policy: FocusPolicy::Pass,
+                    ..Default::default()
+                });
+            }).insert(*button);
+        }
+    }).insert(StartMenu);
+}
+
+fn activate(
+    button: StartButton,
+    state: &mut State<GameState>,
+    exit: &mut EventWriter<AppExit>,
+) {
+    match button {
+        StartButton::Play => state.push(GameState::SelectMenu).unwrap(),
+        StartButton::Settings => state.push(GameState::Settings).unwrap(),
+        StartButton::Records => state.push(GameState::Records).unwrap(),
+        StartButton::Quit => exit.send(AppExit),
+    }
+}
+
+pub fn start_button_interaction(
+    interaction_query: Query<(&Interaction, &StartButton), Changed<Interaction>>,
+    mut state: ResMut<State<GameState>>,
+    mut focus: ResMut<StartMenuFocus>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        let index = START_BUTTONS.iter().position(|(start_button, _)| start_button == button).unwrap();
+        match interaction {
+            Interaction::Clicked => {
+                focus.0 = index;
+                activate(*button, &mut state, &mut exit);
+                return;
+            },
+            Interaction::Hovered => focus.0 = index,
+            Interaction::None => (),
+        }
+    }
+}
+
+pub fn start_menu_keyboard(
+    mut key_input: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+    mut focus: ResMut<StartMenuFocus>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if key_input.just_pressed(KeyCode::Up) && focus.0 > 0 {
+        focus.0 -= 1;
+    }
+    if key_input.just_pressed(KeyCode::Down) && focus.0 + 1 < START_BUTTONS.len() {
+        focus.0 += 1;
+    }
+    if take_key(&mut key_input, KeyCode::Return) {
+        activate(START_BUTTONS[focus.0].0, &mut state, &mut exit);
+    }
+}
+
+pub fn update_start_button_color(
+    mut button_query: Query<(&mut UiColor, &StartButton)>,
+    focus: Res<StartMenuFocus>,
+) {
+    for (mut color, button) in button_query.iter_mut() {
+        color.0 = if *button == START_BUTTONS[focus.0].0 {
+            BUTTON_FOCUS_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+    }
+}
+
+pub fn despawn_start_menu(
+    mut commands: Commands,
+    query: Query<Entity, With<StartMenu>>
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,7 +1,33 @@
+use bevy::prelude::*;
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum GameState {
     StartMenu,
     SelectMenu,
     InGame,
     Result,
-}
\ No newline at end of file
+    ChartError,
+    Settings,
+    Records,
+    KeyBindings,
+    Calibration,
+}
+
+//key가 이번 frame에 눌렸으면 지우고 true를 반환
+//state를 바꾸면 새 state의 on_update가 같은 frame에 돌기 때문에 처리한 키를 지우지 않으면
+//다음 화면이 같은 키를 다시 받아서 Enter, Esc 한 번에 화면이 연달아 넘어감
+pub fn take_key(key_input: &mut Input<KeyCode>, key: KeyCode) -> bool {
+    key_input.clear_just_pressed(key)
+}
+
+//keys 중 하나라도 눌렸으면 모두 지우고 이전 state로 돌아감
+pub fn pop_on_keys(key_input: &mut Input<KeyCode>, state: &mut State<GameState>, keys: &[KeyCode]) -> bool {
+    let mut pressed = false;
+    for &key in keys {
+        pressed |= take_key(key_input, key);
+    }
+    if pressed {
+        state.pop().unwrap();
+    }
+    pressed
+}
@@ -0,0 +1,146 @@
+//BPM이 바뀌는 지점. time은 ms, meter는 한 마디의 박자 수
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimingPoint {
+    pub time: f32,
+    pub bpm: f32,
+    pub meter: u32,
+}
+
+impl TimingPoint {
+    fn beat_length(&self) -> f32 {
+        60000. / self.bpm
+    }
+}
+
+//beat와 ms 사이의 변환. beat 0은 첫 timing point의 time
+//첫 timing point 이전은 첫 BPM으로 연장해서 계산
+#[derive(Clone, Debug)]
+pub struct TimingMap {
+    points: Vec<TimingPoint>,
+    //각 timing point가 시작하는 beat
+    start_beats: Vec<f32>,
+}
+
+impl TimingMap {
+    //points가 비어있으면 안 됨
+    pub fn new(mut points: Vec<TimingPoint>) -> Self {
+        points.sort_by(|a, b| a.time.total_cmp(&b.time));
+        let mut start_beats = Vec::with_capacity(points.len());
+        let mut beat = 0.;
+        for (index, point) in points.iter().enumerate() {
+            if index > 0 {
+                let previous = &points[index - 1];
+                beat += (point.time - previous.time) / previous.beat_length();
+            }
+            start_beats.push(beat);
+        }
+        TimingMap { points, start_beats }
+    }
+
+    //timing point가 없는 chart는 metadata의 BPM, Offset을 하나의 timing point로 사용
+    pub fn from_metadata(bpm: f32, offset: i32) -> Self {
+        TimingMap::new(vec![TimingPoint {
+            time: offset as f32,
+            bpm,
+            meter: 4,
+        }])
+    }
+
+    pub fn beat_to_ms(&self, beat: f32) -> f32 {
+        let index = self.start_beats.iter().rposition(|&start| start <= beat).unwrap_or(0);
+        let point = &self.points[index];
+        point.time + (beat - self.start_beats[index]) * point.beat_length()
+    }
+
+    //'b'로 시작하면 beat(예: b4.5)를 ms로 변환하고, 아니면 ms로 읽음
+    pub fn parse_time(&self, field: &str) -> Option<usize> {
+        match field.strip_prefix('b') {
+            Some(beat) => {
+                let ms = self.beat_to_ms(beat.parse().ok()?);
+                if !ms.is_finite() || ms < 0. {
+                    None
+                } else {
+                    Some(ms.round() as usize)
+                }
+            },
+            None => field.parse().ok(),
+        }
+    }
+
+    //end_ms까지 각 마디가 시작하는 시각(ms). timing point마다 마디를 새로 시작
+    pub fn bar_lines(&self, end_ms: f32) -> Vec<f32> {
+        let mut bar_lines = Vec::new();
+        for (index, point) in self.points.iter().enumerate() {
+            let section_end = self.points.get(index + 1).map_or(end_ms, |next| next.time.min(end_ms));
+            let bar_length = point.beat_length() * point.meter.max(1) as f32;
+            let mut time = point.time;
+            while time <= section_end {
+                //다음 timing point와 겹치는 마디는 다음 section에서 추가
+                if index + 1 < self.points.len() && time >= self.points[index + 1].time {
+                    break;
+                }
+                bar_lines.push(time);
+                //bar_length가 time에 비해 너무 작으면 더해도 time이 그대로라서 멈춤
+                let next = time + bar_length;
+                if next <= time {
+                    break;
+                }
+                time = next;
+            }
+        }
+        bar_lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(time: f32, bpm: f32, meter: u32) -> TimingPoint {
+        TimingPoint { time, bpm, meter }
+    }
+
+    #[test]
+    fn beat_to_ms_follows_bpm_changes() {
+        //120 BPM이면 한 박이 500ms, 2000ms부터 60 BPM이면 1000ms
+        let timing = TimingMap::new(vec![point(2000., 60., 4), point(0., 120., 4)]);
+        assert_eq!(timing.beat_to_ms(0.), 0.);
+        assert_eq!(timing.beat_to_ms(2.), 1000.);
+        assert_eq!(timing.beat_to_ms(4.), 2000.);
+        assert_eq!(timing.beat_to_ms(5.5), 3500.);
+    }
+
+    #[test]
+    fn beats_before_first_point_use_first_bpm() {
+        let timing = TimingMap::from_metadata(120., 1000);
+        assert_eq!(timing.beat_to_ms(-1.), 500.);
+        assert_eq!(timing.beat_to_ms(-2.), 0.);
+    }
+
+    #[test]
+    fn parse_time_reads_ms_and_beats() {
+        let timing = TimingMap::from_metadata(120., 1000);
+        assert_eq!(timing.parse_time("1500"), Some(1500));
+        assert_eq!(timing.parse_time("b2"), Some(2000));
+        assert_eq!(timing.parse_time("b0.5"), Some(1250));
+        //0ms보다 이르거나 숫자가 아니면 실패
+        assert_eq!(timing.parse_time("b-3"), None);
+        assert_eq!(timing.parse_time("bNaN"), None);
+        assert_eq!(timing.parse_time("-5"), None);
+        assert_eq!(timing.parse_time("abc"), None);
+    }
+
+    #[test]
+    fn bar_lines_restart_at_each_point() {
+        //120 BPM 4/4는 한 마디 2000ms, 60 BPM 3/4는 3000ms
+        let timing = TimingMap::new(vec![point(0., 120., 4), point(3000., 60., 3)]);
+        assert_eq!(timing.bar_lines(7000.), vec![0., 2000., 3000., 6000.]);
+    }
+
+    #[test]
+    fn bar_lines_stop_when_bar_is_too_short() {
+        //마디 길이가 time에 더해도 바뀌지 않을 만큼 작으면 한 줄만 만들고 멈춤
+        let timing = TimingMap::new(vec![point(297., 1e12, 4)]);
+        assert_eq!(timing.bar_lines(1000.), vec![297.]);
+    }
+}
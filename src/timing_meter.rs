@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+use crate::state::GameState;
+use crate::notes::{InGameEntity, JudgeAccuracy, JUDGE_LINE};
+use crate::judge::{HitEvent, HitKind, JudgeWindows};
+use crate::settings::Settings;
+
+//판정선 아래에 누른 시각이 빠르고 늦은 정도를 표시하는 막대
+const METER_Y: f32 = JUDGE_LINE - 45.;
+const METER_WIDTH: f32 = 300.;
+const METER_HEIGHT: f32 = 8.;
+const TICK_WIDTH: f32 = 2.;
+const TICK_HEIGHT: f32 = 20.;
+//tick이 완전히 사라지기까지의 시간(초)
+const TICK_LIFETIME: f32 = 3.;
+const BAND_ALPHA: f32 = 0.3;
+const CENTER_COLOR: Color = Color::rgba(0.98, 0.98, 0.98, 0.8);
+
+pub struct TimingMeterPlugin;
+
+impl Plugin for TimingMeterPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame)
+                .with_system(setup_timing_meter)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                .with_system(spawn_meter_tick)
+                .with_system(fade_meter_tick)
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct MeterTick {
+    timer: Timer,
+    color: Color,
+}
+
+fn grade_color(accuracy: JudgeAccuracy) -> Color {
+    match accuracy {
+        JudgeAccuracy::Perfect => Color::rgb(0.4, 0.85, 1.),
+        JudgeAccuracy::Great => Color::rgb(0.45, 0.95, 0.45),
+        JudgeAccuracy::Good => Color::rgb(0.95, 0.85, 0.3),
+        JudgeAccuracy::Bad => Color::rgb(0.95, 0.5, 0.2),
+        _ => Color::rgb(0.9, 0.2, 0.2),
+    }
+}
+
+//막대의 양 끝이 누르기 miss window
+fn pixels_per_second(judge_windows: &JudgeWindows) -> f32 {
+    METER_WIDTH / (2. * judge_windows.press.miss)
+}
+
+//on_enter 순서에 상관없이 이번 판의 판정 범위를 쓰도록 설정에서 직접 만듦
+pub fn setup_timing_meter(
+    mut commands: Commands,
+    settings: Res<Settings>,
+) {
+    let judge_windows = JudgeWindows::from_preset(settings.judge_preset);
+    let press = judge_windows.press;
+    let scale = pixels_per_second(&judge_windows);
+    //넓은 window부터 그려서 좁은 window가 위에 보이게 함
+    let bands = [
+        (press.miss, JudgeAccuracy::Miss),
+        (press.bad, JudgeAccuracy::Bad),
+        (press.good, JudgeAccuracy::Good),
+        (press.great, JudgeAccuracy::Great),
+        (press.perfect, JudgeAccuracy::Perfect),
+    ];
+    for (index, (window, accuracy)) in bands.iter().enumerate() {
+        let mut color = grade_color(*accuracy);
+        color.set_a(BAND_ALPHA);
+        commands.spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::new(2. * window * scale, METER_HEIGHT)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(Vec3::new(0., METER_Y, 3. + index as f32 * 0.01)),
+            ..Default::default()
+        }).insert(InGameEntity);
+    }
+    commands.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            color: CENTER_COLOR,
+            custom_size: Some(Vec2::new(TICK_WIDTH, TICK_HEIGHT)),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(Vec3::new(0., METER_Y, 3.1)),
+        ..Default::default()
+    }).insert(InGameEntity);
+}
+
+pub fn spawn_meter_tick(
+    mut commands: Commands,
+    mut events: EventReader<HitEvent>,
+    judge_windows: Res<JudgeWindows>,
+) {
+    let scale = pixels_per_second(&judge_windows);
+    for hit in events.iter().filter(|hit| hit.kind == HitKind::Press) {
+        let x = (hit.offset / 1000. * scale).clamp(-METER_WIDTH / 2., METER_WIDTH / 2.);
+        let color = grade_color(hit.accuracy);
+        commands.spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::new(TICK_WIDTH, TICK_HEIGHT)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(Vec3::new(x, METER_Y, 3.2)),
+            ..Default::default()
+        }).insert(InGameEntity).insert(MeterTick {
+            timer: Timer::from_seconds(TICK_LIFETIME, false),
+            color,
+        });
+    }
+}
+
+pub fn fade_meter_tick(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Sprite, &mut MeterTick)>,
+) {
+    for (entity, mut sprite, mut tick) in query.iter_mut() {
+        tick.timer.tick(time.delta());
+        if tick.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let mut color = tick.color;
+        color.set_a(tick.color.a() * tick.timer.percent_left());
+        sprite.color = color;
+    }
+}